@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+use shardz_manifests::{instantiate_manifest, render, stats_manifest, AdminManifests};
+
+/// Generates the manifests to deploy and operate a Shardz component, to be signed offline or submitted with a wallet
+#[derive(Parser)]
+#[command(name = "shardz-cli", version)]
+struct Cli {
+    #[arg(long, value_enum, default_value_t = Network::Simulator)]
+    network: Network,
+    /// Account paying the fees and holding the owner badge
+    #[arg(long)]
+    account: String,
+    /// Fee locked from the account, leave unset when the wallet adds its own fee lock
+    #[arg(long)]
+    fee: Option<Decimal>,
+    /// Writes the manifest to this file instead of printing it
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Simulator,
+    Stokenet,
+    Mainnet,
+}
+
+impl Network {
+    fn definition(self) -> NetworkDefinition {
+        match self {
+            Network::Simulator => NetworkDefinition::simulator(),
+            Network::Stokenet => NetworkDefinition::stokenet(),
+            Network::Mainnet => NetworkDefinition::mainnet(),
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct Target {
+    #[arg(long)]
+    component: String,
+    /// Owner badge, also required by the drawer role
+    #[arg(long)]
+    badge: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Instantiates a component from a published package
+    Deploy {
+        #[arg(long)]
+        package: String,
+        /// Dapp definition account, defaults to the fee account
+        #[arg(long)]
+        dapp_definition: Option<String>,
+        /// Hex encoded head of the drawer's seed chain, as printed by `shardz-drawer commitment`
+        #[arg(long)]
+        seed_commitment: Hash,
+    },
+    /// Draws tickets, revealing the seed of the current commitment if given
+    Draw {
+        #[command(flatten)]
+        target: Target,
+        /// Hex encoded seed revealing the current commitment
+        #[arg(long)]
+        reveal: Option<Hash>,
+        /// Integer ids of the tickets to draw
+        #[arg(required = true)]
+        tickets: Vec<u64>,
+    },
+    /// Starts a new seed chain of the drawer
+    CommitSeed {
+        #[command(flatten)]
+        target: Target,
+        /// Hex encoded head of the new seed chain
+        commitment: Hash,
+    },
+    /// Pauses or resumes bonding, swapping and rerolling
+    Pause {
+        #[command(flatten)]
+        target: Target,
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Sets the XRD royalty of a method
+    SetRoyalty {
+        #[command(flatten)]
+        target: Target,
+        method: String,
+        amount: Decimal,
+    },
+    /// Withdraws from the treasury, or claims the component royalties with `--royalties`
+    Withdraw {
+        #[command(flatten)]
+        target: Target,
+        #[arg(long, conflicts_with = "royalties")]
+        resource: Option<String>,
+        #[arg(long, conflicts_with = "royalties")]
+        amount: Option<Decimal>,
+        #[arg(long)]
+        royalties: bool,
+    },
+    /// Sets a string metadata of the component, with the owner badge
+    SetMetadata {
+        #[command(flatten)]
+        target: Target,
+        key: String,
+        value: String,
+    },
+    /// Reads the component statistics, the manifest is meant to be previewed
+    Stats {
+        #[arg(long)]
+        component: String,
+    },
+}
+
+struct Addresses {
+    decoder: AddressBech32Decoder,
+}
+
+impl Addresses {
+    fn component(&self, address: &str) -> ComponentAddress {
+        ComponentAddress::try_from_bech32(&self.decoder, address).unwrap_or_else(|| exit(&format!("Invalid component address {}", address)))
+    }
+
+    fn resource(&self, address: &str) -> ResourceAddress {
+        ResourceAddress::try_from_bech32(&self.decoder, address).unwrap_or_else(|| exit(&format!("Invalid resource address {}", address)))
+    }
+
+    fn package(&self, address: &str) -> PackageAddress {
+        PackageAddress::try_from_bech32(&self.decoder, address).unwrap_or_else(|| exit(&format!("Invalid package address {}", address)))
+    }
+
+    fn admin(&self, target: &Target, account: ComponentAddress, fee: Option<Decimal>) -> AdminManifests {
+        AdminManifests {
+            component: self.component(&target.component),
+            badge: self.resource(&target.badge),
+            account,
+            fee,
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let network = cli.network.definition();
+    let addresses = Addresses { decoder: AddressBech32Decoder::new(&network) };
+    let account = addresses.component(&cli.account);
+
+    let manifest = match &cli.command {
+        Command::Deploy { package, dapp_definition, seed_commitment } => {
+            let dapp_definition = dapp_definition.as_deref().map(|address| addresses.component(address)).unwrap_or(account);
+            instantiate_manifest(addresses.package(package), dapp_definition, *seed_commitment, account, cli.fee)
+        }
+        Command::Draw { target, reveal, tickets } => {
+            let ticket_ids = tickets.iter().map(|id| NonFungibleLocalId::integer(*id)).collect();
+            addresses.admin(target, account, cli.fee).draw_manifest(ticket_ids, *reveal)
+        }
+        Command::CommitSeed { target, commitment } => addresses.admin(target, account, cli.fee).commit_seed_manifest(*commitment),
+        Command::Pause { target, resume } => addresses.admin(target, account, cli.fee).set_paused_manifest(!resume),
+        Command::SetRoyalty { target, method, amount } => {
+            addresses.admin(target, account, cli.fee).set_royalty_manifest(method, RoyaltyAmount::Xrd(*amount))
+        }
+        Command::Withdraw { target, resource, amount, royalties } => {
+            let admin = addresses.admin(target, account, cli.fee);
+            match (royalties, resource, amount) {
+                (true, _, _) => admin.claim_royalties_manifest(),
+                (false, Some(resource), Some(amount)) => admin.withdraw_treasury_manifest(addresses.resource(resource), *amount),
+                _ => exit("Withdraw requires --resource and --amount, or --royalties"),
+            }
+        }
+        Command::SetMetadata { target, key, value } => addresses.admin(target, account, cli.fee).set_metadata_manifest(key, value.clone()),
+        Command::Stats { component } => stats_manifest(addresses.component(component)),
+    };
+
+    let rtm = render(&manifest, &network).unwrap_or_else(|error| exit(&format!("Could not render the manifest: {:?}", error)));
+    match cli.output {
+        Some(path) => fs::write(&path, rtm).unwrap_or_else(|error| exit(&format!("Could not write {}: {}", path.display(), error))),
+        None => println!("{}", rtm),
+    }
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use radix_transactions::prelude::TransactionManifestV1;
+use scrypto::prelude::*;
+
+#[derive(Debug)]
+pub enum DrawerError {
+    /// The ledger could not be reached or returned an unexpected response
+    Ledger(String),
+    /// The transaction was committed as a failure or rejected
+    Rejected(String),
+    /// A value returned by the ledger could not be decoded
+    Decode(String),
+    /// The current seed commitment of the component is not part of the drawer's seed chain
+    UnknownCommitment(Hash),
+}
+
+impl fmt::Display for DrawerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawerError::Ledger(message) => write!(f, "ledger error: {}", message),
+            DrawerError::Rejected(message) => write!(f, "transaction rejected: {}", message),
+            DrawerError::Decode(message) => write!(f, "decode error: {}", message),
+            DrawerError::UnknownCommitment(commitment) => {
+                write!(f, "seed commitment {} is not part of the seed chain, commit a new chain with commit_seed", commitment)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DrawerError {}
+
+/// Access to a ledger running the Shardz component, either through the Gateway, a test ledger or a mock
+pub trait LedgerClient {
+    /// Undrawn tickets in bonding order, as returned by the `pending_tickets` method
+    fn pending_tickets(&mut self, limit: u32) -> Result<Vec<NonFungibleLocalId>, DrawerError>;
+
+    /// Commitment that tickets bonded now are drawn with, as returned by the `seed_commitment` method
+    fn seed_commitment(&mut self) -> Result<Hash, DrawerError>;
+
+    /// Submits a manifest and waits for it to be committed
+    fn submit(&mut self, manifest: TransactionManifestV1) -> Result<(), DrawerError>;
+}
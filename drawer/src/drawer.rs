@@ -0,0 +1,113 @@
+use radix_shardz::shardz::SeedChain;
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+use shardz_manifests::AdminManifests;
+
+use crate::client::{DrawerError, LedgerClient};
+
+pub struct DrawerConfig {
+    pub component: ComponentAddress,
+    /// Account holding the badge of the drawer role and paying the fees
+    pub account: ComponentAddress,
+    pub badge: ResourceAddress,
+    pub batch_size: u32,
+    pub fee: Decimal,
+}
+
+impl DrawerConfig {
+    pub fn new(component: ComponentAddress, account: ComponentAddress, badge: ResourceAddress) -> Self {
+        Self {
+            component,
+            account,
+            badge,
+            batch_size: 50,
+            fee: dec!(10),
+        }
+    }
+
+    /// Manifests of the drawer role, proving the badge held by the drawer account
+    pub fn manifests(&self) -> AdminManifests {
+        AdminManifests {
+            component: self.component,
+            badge: self.badge,
+            account: self.account,
+            fee: Some(self.fee),
+        }
+    }
+}
+
+pub struct Drawer<C: LedgerClient> {
+    client: C,
+    config: DrawerConfig,
+    seed_chain: SeedChain,
+}
+
+impl<C: LedgerClient> Drawer<C> {
+    /// The head of `seed_chain` must be the commitment the component was instantiated with or set with `commit_seed`
+    pub fn new(client: C, config: DrawerConfig, seed_chain: SeedChain) -> Self {
+        Self {
+            client,
+            config,
+            seed_chain,
+        }
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Draws the next batch of pending tickets, returns an empty list when the queue is empty.
+    /// Every batch reveals the seed of the current commitment, so the tickets bonded before it can all be drawn.
+    pub fn draw_pending(&mut self) -> Result<Vec<NonFungibleLocalId>, DrawerError> {
+        let pending = self.client.pending_tickets(self.config.batch_size)?;
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Read after the pending tickets, so that none of them was bonded with a later commitment
+        let commitment = self.client.seed_commitment()?;
+        let reveal = self.seed_chain.reveal(&commitment)
+            .ok_or(DrawerError::UnknownCommitment(commitment))?;
+
+        self.client.submit(self.config.manifests().draw_manifest(pending.clone(), Some(reveal)))?;
+
+        Ok(pending)
+    }
+
+    /// Draws batches until no ticket is pending
+    pub fn drain(&mut self) -> Result<Vec<NonFungibleLocalId>, DrawerError> {
+        let mut ticket_ids = Vec::new();
+        loop {
+            let batch = self.draw_pending()?;
+            if batch.is_empty() {
+                return Ok(ticket_ids);
+            }
+            ticket_ids.extend(batch);
+        }
+    }
+}
+
+/// Badge proven, tickets and revealed seed passed to `draw_batch` by a manifest built with `AdminManifests::draw_manifest`
+pub fn decode_draw_manifest(manifest: &TransactionManifestV1, component: ComponentAddress) -> Result<(Option<ResourceAddress>, Vec<NonFungibleLocalId>, Option<Hash>), DrawerError> {
+    let component = DynamicGlobalAddress::Static(component.into());
+    let mut badge: Option<ResourceAddress> = None;
+
+    for instruction in manifest.instructions.iter() {
+        if let InstructionV1::CallMethod { address, method_name, args } = instruction {
+            if method_name == "create_proof_of_amount" {
+                let (resource_address, _): (ResourceAddress, Decimal) = decode_args(args)?;
+                badge = Some(resource_address);
+            } else if *address == component && method_name == "draw_batch" {
+                let (ticket_ids, reveal): (Vec<NonFungibleLocalId>, Option<Hash>) = decode_args(args)?;
+                return Ok((badge, ticket_ids, reveal));
+            }
+        }
+    }
+
+    Err(DrawerError::Rejected("Manifest does not call draw_batch".to_string()))
+}
+
+fn decode_args<T: ManifestDecode>(args: &ManifestValue) -> Result<T, DrawerError> {
+    let bytes = manifest_encode(args).map_err(|error| DrawerError::Decode(format!("{:?}", error)))?;
+    manifest_decode(&bytes).map_err(|error| DrawerError::Decode(format!("{:?}", error)))
+}
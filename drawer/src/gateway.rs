@@ -0,0 +1,150 @@
+use std::thread;
+use std::time::Duration;
+
+use radix_transactions::manifest::decompile;
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+use serde_json::{json, Value};
+
+use crate::client::{DrawerError, LedgerClient};
+
+/// Number of epochs a submitted transaction stays valid for
+const EPOCH_WINDOW: u64 = 10;
+
+/// Reads the component state with transaction previews and submits notarized transactions through the Gateway API
+pub struct GatewayClient {
+    http: reqwest::blocking::Client,
+    url: String,
+    network: NetworkDefinition,
+    component: ComponentAddress,
+    private_key: Ed25519PrivateKey,
+    poll_interval: Duration,
+}
+
+impl GatewayClient {
+    pub fn new(url: &str, network: NetworkDefinition, component: ComponentAddress, private_key: Ed25519PrivateKey) -> Self {
+        Self {
+            http: reqwest::blocking::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            network,
+            component,
+            private_key,
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+
+    fn post(&self, path: &str, body: Value) -> Result<Value, DrawerError> {
+        let response = self.http.post(format!("{}{}", self.url, path))
+            .json(&body)
+            .send()
+            .map_err(|error| DrawerError::Ledger(error.to_string()))?;
+
+        let status = response.status();
+        let body: Value = response.json().map_err(|error| DrawerError::Ledger(error.to_string()))?;
+        if !status.is_success() {
+            return Err(DrawerError::Ledger(format!("{} {}: {}", path, status, body)));
+        }
+
+        Ok(body)
+    }
+
+    fn current_epoch(&self) -> Result<u64, DrawerError> {
+        let status = self.post("/status/gateway-status", json!({}))?;
+        status["ledger_state"]["epoch"].as_u64()
+            .ok_or_else(|| DrawerError::Decode("missing ledger epoch".to_string()))
+    }
+
+    fn manifest_string(&self, manifest: &TransactionManifestV1) -> Result<String, DrawerError> {
+        decompile(&manifest.instructions, &self.network)
+            .map_err(|error| DrawerError::Decode(format!("{:?}", error)))
+    }
+
+    fn wait_for_commit(&self, intent_hash: &str) -> Result<(), DrawerError> {
+        loop {
+            let status = self.post("/transaction/status", json!({ "intent_hash": intent_hash }))?;
+            match status["intent_status"].as_str() {
+                Some("CommittedSuccess") => return Ok(()),
+                Some("CommittedFailure") | Some("PermanentlyRejected") => {
+                    let message = status["error_message"].as_str().unwrap_or("unknown error");
+                    return Err(DrawerError::Rejected(format!("{}: {}", intent_hash, message)));
+                }
+                _ => thread::sleep(self.poll_interval),
+            }
+        }
+    }
+
+    /// Previews a call to a read-only method of the component and decodes its output
+    fn preview<T: ScryptoDecode>(&self, method_name: &str, args: ManifestArgs) -> Result<T, DrawerError> {
+        let manifest = ManifestBuilder::new()
+            .call_method(self.component, method_name, args)
+            .build();
+        let epoch = self.current_epoch()?;
+
+        let preview = self.post("/transaction/preview", json!({
+            "manifest": self.manifest_string(&manifest)?,
+            "start_epoch_inclusive": epoch,
+            "end_epoch_exclusive": epoch + EPOCH_WINDOW,
+            "tip_percentage": 0,
+            "nonce": rand::random::<u32>(),
+            "signer_public_keys": [],
+            "flags": {
+                "use_free_credit": true,
+                "assume_all_signature_proofs": true,
+                "skip_epoch_check": false
+            }
+        }))?;
+
+        let receipt = &preview["receipt"];
+        if receipt["status"].as_str() != Some("Succeeded") {
+            return Err(DrawerError::Rejected(format!("{} preview failed: {}", method_name, receipt["error_message"])));
+        }
+
+        let output = receipt["output"][0]["hex"].as_str()
+            .ok_or_else(|| DrawerError::Decode(format!("missing {} output", method_name)))?;
+        let bytes = hex::decode(output).map_err(|error| DrawerError::Decode(error.to_string()))?;
+
+        scrypto_decode(&bytes).map_err(|error| DrawerError::Decode(format!("{:?}", error)))
+    }
+}
+
+impl LedgerClient for GatewayClient {
+    fn pending_tickets(&mut self, limit: u32) -> Result<Vec<NonFungibleLocalId>, DrawerError> {
+        self.preview("pending_tickets", manifest_args!(limit))
+    }
+
+    fn seed_commitment(&mut self) -> Result<Hash, DrawerError> {
+        self.preview("seed_commitment", manifest_args!())
+    }
+
+    fn submit(&mut self, manifest: TransactionManifestV1) -> Result<(), DrawerError> {
+        let epoch = self.current_epoch()?;
+
+        let transaction = TransactionBuilder::new()
+            .header(TransactionHeaderV1 {
+                network_id: self.network.id,
+                start_epoch_inclusive: Epoch::of(epoch),
+                end_epoch_exclusive: Epoch::of(epoch + EPOCH_WINDOW),
+                nonce: rand::random::<u32>(),
+                notary_public_key: self.private_key.public_key().into(),
+                notary_is_signatory: true,
+                tip_percentage: 0,
+            })
+            .manifest(manifest)
+            .notarize(&self.private_key)
+            .build();
+
+        let intent_hash = transaction.prepare()
+            .map_err(|error| DrawerError::Decode(format!("{:?}", error)))?
+            .intent_hash();
+        let intent_hash = TransactionHashBech32Encoder::new(&self.network)
+            .encode(&intent_hash)
+            .map_err(|error| DrawerError::Decode(format!("{:?}", error)))?;
+
+        let payload = transaction.to_payload_bytes()
+            .map_err(|error| DrawerError::Decode(format!("{:?}", error)))?;
+        self.post("/transaction/submit", json!({ "notarized_transaction_hex": hex::encode(payload) }))?;
+
+        self.wait_for_commit(&intent_hash)
+    }
+}
+
@@ -0,0 +1,4 @@
+pub mod client;
+pub mod drawer;
+pub mod gateway;
+pub mod mock;
@@ -0,0 +1,79 @@
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+use radix_shardz::shardz::SeedChain;
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+use shardz_drawer::drawer::{Drawer, DrawerConfig};
+use shardz_drawer::gateway::GatewayClient;
+
+/// Polls the Gateway for undrawn tickets and draws them, configured through environment variables:
+/// - SHARDZ_NETWORK: `mainnet` or `stokenet`
+/// - SHARDZ_GATEWAY_URL: Gateway API url, defaults to the public Gateway of the network
+/// - SHARDZ_COMPONENT, SHARDZ_ACCOUNT, SHARDZ_BADGE: addresses of the component, the drawer account and its badge
+/// - SHARDZ_PRIVATE_KEY: hex encoded Ed25519 key of the drawer account, used to notarize the transactions
+/// - SHARDZ_SEED: hex encoded 32 bytes secret the seed chain is built from, it must be kept private
+/// - SHARDZ_CHAIN_LENGTH: optional number of batches the seed chain can reveal, defaults to 10 000
+/// - SHARDZ_POLL_SECONDS, SHARDZ_BATCH_SIZE: optional, default to 10 seconds and 50 tickets
+///
+/// `shardz-drawer commitment` only prints the head of the seed chain, to pass when instantiating the component or to
+/// `commit_seed`.
+fn main() {
+    let secret: [u8; 32] = hex::decode(var("SHARDZ_SEED")).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .expect("Seed must be 32 hex encoded bytes");
+    let chain_length = env::var("SHARDZ_CHAIN_LENGTH").map(|s| s.parse().expect("Invalid chain length")).unwrap_or(10_000);
+    let seed_chain = SeedChain::new(secret, chain_length);
+
+    if env::args().nth(1).as_deref() == Some("commitment") {
+        println!("{}", seed_chain.head());
+        return;
+    }
+
+    let network = match var("SHARDZ_NETWORK").as_str() {
+        "mainnet" => NetworkDefinition::mainnet(),
+        "stokenet" => NetworkDefinition::stokenet(),
+        other => panic!("Unsupported network {}", other),
+    };
+    let gateway_url = env::var("SHARDZ_GATEWAY_URL").unwrap_or_else(|_| match network.id {
+        1 => "https://mainnet.radixdlt.com".to_string(),
+        _ => "https://stokenet.radixdlt.com".to_string(),
+    });
+
+    let decoder = AddressBech32Decoder::new(&network);
+    let component = ComponentAddress::try_from_bech32(&decoder, &var("SHARDZ_COMPONENT")).expect("Invalid component address");
+    let account = ComponentAddress::try_from_bech32(&decoder, &var("SHARDZ_ACCOUNT")).expect("Invalid account address");
+    let badge = ResourceAddress::try_from_bech32(&decoder, &var("SHARDZ_BADGE")).expect("Invalid badge address");
+
+    let key_bytes = hex::decode(var("SHARDZ_PRIVATE_KEY")).expect("Invalid private key");
+    let private_key = Ed25519PrivateKey::from_bytes(&key_bytes).expect("Invalid private key");
+
+    let mut config = DrawerConfig::new(component, account, badge);
+    if let Ok(batch_size) = env::var("SHARDZ_BATCH_SIZE") {
+        config.batch_size = batch_size.parse().expect("Invalid batch size");
+    }
+    let poll_interval = Duration::from_secs(env::var("SHARDZ_POLL_SECONDS").map(|s| s.parse().expect("Invalid poll interval")).unwrap_or(10));
+
+    let client = GatewayClient::new(&gateway_url, network, component, private_key);
+    let mut drawer = Drawer::new(client, config, seed_chain);
+
+    loop {
+        match drawer.draw_pending() {
+            Ok(ticket_ids) if ticket_ids.is_empty() => thread::sleep(poll_interval),
+            Ok(ticket_ids) => {
+                for ticket_id in ticket_ids {
+                    println!("Drew ticket {}", ticket_id);
+                }
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                thread::sleep(poll_interval);
+            }
+        }
+    }
+}
+
+fn var(name: &str) -> String {
+    env::var(name).unwrap_or_else(|_| panic!("{} is not set", name))
+}
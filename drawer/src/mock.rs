@@ -0,0 +1,136 @@
+use radix_shardz::shardz::{default_tier_weights, draw_tier, seed_commitment, ticket_seed, ShardType};
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+
+use crate::client::{DrawerError, LedgerClient};
+use crate::drawer::decode_draw_manifest;
+
+/// Ticket of the mock: commitment and bond hash it was bonded with, and its tier once drawn
+struct MockTicket {
+    seed_commitment: Hash,
+    bond_hash: Hash,
+    shard_type: Option<ShardType>,
+}
+
+/// In-memory mock of the Shardz component, applying `draw_batch` manifests with the blueprint's draw functions.
+/// It ignores profiles, seasons and expiry: `tests/lib.rs` also runs the drawer against the real blueprint.
+pub struct MockClient {
+    component: ComponentAddress,
+    badge: ResourceAddress,
+    tier_weights: Vec<(ShardType, u32)>,
+    seed_commitment: Hash,
+    seed_reveals: IndexMap<Hash, Hash>,
+    tickets: IndexMap<NonFungibleLocalId, MockTicket>,
+    ticket_counter: u64,
+    submitted: Vec<TransactionManifestV1>,
+}
+
+impl MockClient {
+    pub fn new(component: ComponentAddress, badge: ResourceAddress, seed_commitment: Hash) -> Self {
+        Self {
+            component,
+            badge,
+            tier_weights: default_tier_weights(),
+            seed_commitment,
+            seed_reveals: IndexMap::new(),
+            tickets: IndexMap::new(),
+            ticket_counter: 1,
+            submitted: Vec::new(),
+        }
+    }
+
+    pub fn set_tier_weights(&mut self, tier_weights: Vec<(ShardType, u32)>) {
+        self.tier_weights = tier_weights;
+    }
+
+    /// Bonds undrawn tickets with the current commitment and returns their ids
+    pub fn bond(&mut self, count: u64) -> Vec<NonFungibleLocalId> {
+        (0..count)
+            .map(|_| {
+                let ticket_id = NonFungibleLocalId::integer(self.ticket_counter);
+                let ticket = MockTicket {
+                    seed_commitment: self.seed_commitment,
+                    // Stands in for the hash of the bonding transaction
+                    bond_hash: hash(self.ticket_counter.to_le_bytes()),
+                    shard_type: None,
+                };
+                self.ticket_counter += 1;
+                self.tickets.insert(ticket_id.clone(), ticket);
+                ticket_id
+            })
+            .collect()
+    }
+
+    pub fn shard_type(&self, ticket_id: &NonFungibleLocalId) -> Option<ShardType> {
+        self.tickets.get(ticket_id).and_then(|ticket| ticket.shard_type.clone())
+    }
+
+    pub fn current_commitment(&self) -> Hash {
+        self.seed_commitment
+    }
+
+    pub fn submitted(&self) -> &[TransactionManifestV1] {
+        &self.submitted
+    }
+
+    fn draw_batch(&mut self, ticket_ids: Vec<NonFungibleLocalId>, reveal: Option<Hash>) -> Result<(), DrawerError> {
+        if let Some(seed) = reveal {
+            if seed_commitment(&seed) != self.seed_commitment {
+                return Err(DrawerError::Rejected("The revealed seed does not match the seed commitment".to_string()));
+            }
+        }
+        let revealed = |commitment: &Hash| match reveal {
+            Some(seed) if seed_commitment(&seed) == *commitment => Some(seed),
+            _ => self.seed_reveals.get(commitment).copied(),
+        };
+
+        let mut seeds = Vec::new();
+        for ticket_id in ticket_ids.iter() {
+            let ticket = self.tickets.get(ticket_id)
+                .ok_or_else(|| DrawerError::Rejected(format!("Ticket {} does not exist", ticket_id)))?;
+            if ticket.shard_type.is_some() {
+                return Err(DrawerError::Rejected(format!("Ticket {} was already drawn", ticket_id)));
+            }
+            let seed = revealed(&ticket.seed_commitment)
+                .ok_or_else(|| DrawerError::Rejected(format!("The seed of ticket {} has not been revealed", ticket_id)))?;
+            seeds.push(ticket_seed(&seed, &ticket.bond_hash));
+        }
+
+        if let Some(seed) = reveal {
+            self.seed_reveals.insert(self.seed_commitment, seed);
+            self.seed_commitment = seed;
+        }
+        for (ticket_id, seed) in ticket_ids.into_iter().zip(seeds) {
+            let shard_type = draw_tier(&self.tier_weights, &ticket_id, seed);
+            self.tickets.get_mut(&ticket_id).unwrap().shard_type = Some(shard_type);
+        }
+
+        Ok(())
+    }
+}
+
+impl LedgerClient for MockClient {
+    fn pending_tickets(&mut self, limit: u32) -> Result<Vec<NonFungibleLocalId>, DrawerError> {
+        Ok(self.tickets.iter()
+            .filter(|(_, ticket)| ticket.shard_type.is_none())
+            .map(|(ticket_id, _)| ticket_id.clone())
+            .take(limit as usize)
+            .collect())
+    }
+
+    fn seed_commitment(&mut self) -> Result<Hash, DrawerError> {
+        Ok(self.seed_commitment)
+    }
+
+    fn submit(&mut self, manifest: TransactionManifestV1) -> Result<(), DrawerError> {
+        let (badge, ticket_ids, reveal) = decode_draw_manifest(&manifest, self.component)?;
+        if badge != Some(self.badge) {
+            return Err(DrawerError::Rejected("Unauthorized".to_string()));
+        }
+
+        self.draw_batch(ticket_ids, reveal)?;
+        self.submitted.push(manifest);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+#[cfg(test)]
+mod drawer_tests {
+    use radix_shardz::shardz::SeedChain;
+    use scrypto::prelude::*;
+    use shardz_drawer::client::{DrawerError, LedgerClient};
+    use shardz_drawer::drawer::{Drawer, DrawerConfig};
+    use shardz_drawer::mock::MockClient;
+
+    const COMPONENT: ComponentAddress = ComponentAddress::new_or_panic([192; 30]);
+    const ACCOUNT: ComponentAddress = ComponentAddress::new_or_panic([193; 30]);
+    const BADGE: ResourceAddress = ResourceAddress::new_or_panic([93; 30]);
+
+    fn drawer(tickets: u64, batch_size: u32, secret: [u8; 32]) -> Drawer<MockClient> {
+        let seed_chain = SeedChain::new(secret, 100);
+        let mut client = MockClient::new(COMPONENT, BADGE, seed_chain.head());
+        client.bond(tickets);
+
+        let mut config = DrawerConfig::new(COMPONENT, ACCOUNT, BADGE);
+        config.batch_size = batch_size;
+
+        Drawer::new(client, config, seed_chain)
+    }
+
+    #[test]
+    fn test_drain_pending_tickets() {
+        let mut drawer = drawer(120, 50, [7; 32]);
+
+        let ticket_ids = drawer.drain().unwrap();
+        assert_eq!(ticket_ids.len(), 120);
+        assert_eq!(drawer.client().submitted().len(), 3);
+
+        for ticket_id in ticket_ids.iter() {
+            assert!(drawer.client().shard_type(ticket_id).is_some());
+        }
+        assert!(drawer.draw_pending().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_each_batch_reveals_the_next_seed() {
+        let seed_chain = SeedChain::new([7; 32], 100);
+        let mut drawer = drawer(120, 50, [7; 32]);
+        drawer.drain().unwrap();
+
+        // Three batches walk three seeds down the chain
+        let mut commitment = seed_chain.head();
+        for _ in 0..3 {
+            commitment = seed_chain.reveal(&commitment).unwrap();
+        }
+        assert_eq!(drawer.client().current_commitment(), commitment);
+    }
+
+    #[test]
+    fn test_seeded_draws_are_reproducible() {
+        let shard_types = |secret: [u8; 32]| {
+            let mut drawer = drawer(20, 50, secret);
+            let ticket_ids = drawer.drain().unwrap();
+            ticket_ids.iter().map(|ticket_id| drawer.client().shard_type(ticket_id)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(shard_types([1; 32]), shard_types([1; 32]));
+        assert_ne!(shard_types([1; 32]), shard_types([2; 32]));
+    }
+
+    #[test]
+    fn test_unknown_commitment_fails() {
+        let mut client = MockClient::new(COMPONENT, BADGE, SeedChain::new([2; 32], 100).head());
+        client.bond(5);
+        let mut drawer = Drawer::new(client, DrawerConfig::new(COMPONENT, ACCOUNT, BADGE), SeedChain::new([1; 32], 100));
+
+        assert!(matches!(drawer.draw_pending(), Err(DrawerError::UnknownCommitment(_))));
+        assert!(drawer.client().submitted().is_empty());
+    }
+
+    #[test]
+    fn test_draw_requires_badge_proof() {
+        let seed_chain = SeedChain::new([7; 32], 100);
+        let mut client = MockClient::new(COMPONENT, BADGE, seed_chain.head());
+        let ticket_id = client.bond(1).pop().unwrap();
+
+        let ticket_ids = vec![ticket_id.clone()];
+        let reveal = seed_chain.reveal(&seed_chain.head());
+        let other_badge = ResourceAddress::new_or_panic([154; 30]);
+        let manifest = DrawerConfig::new(COMPONENT, ACCOUNT, other_badge).manifests().draw_manifest(ticket_ids.clone(), reveal);
+
+        assert!(matches!(client.submit(manifest), Err(DrawerError::Rejected(_))));
+        assert_eq!(client.shard_type(&ticket_id), None);
+
+        let manifests = DrawerConfig::new(COMPONENT, ACCOUNT, BADGE).manifests();
+        client.submit(manifests.draw_manifest(ticket_ids.clone(), reveal)).unwrap();
+        assert!(client.shard_type(&ticket_id).is_some());
+        assert!(matches!(client.submit(manifests.draw_manifest(ticket_ids, None)), Err(DrawerError::Rejected(_))));
+    }
+}
+
+#[cfg(test)]
+mod ledger_tests {
+    use radix_shardz::shardz::{default_tier_weights, draw_tier, ticket_seed, PityConfig, Referrer, SeedChain, ShardTicket, ShardType};
+    use scrypto_test::prelude::*;
+    use shardz_drawer::client::{DrawerError, LedgerClient};
+    use shardz_drawer::drawer::{Drawer, DrawerConfig};
+    use shardz_manifests::simulator::{ShardzSimulator, SHARDZ_BADGE};
+
+    /// Client executing the drawer's manifests as they are against the real blueprint in a ledger simulator
+    struct SimulatorClient<'a> {
+        ledger: &'a mut DefaultLedgerSimulator,
+        component: ComponentAddress,
+        public_key: Secp256k1PublicKey,
+    }
+
+    impl<'a> SimulatorClient<'a> {
+        fn call<T: ScryptoDecode>(&mut self, method_name: &str, args: ManifestArgs) -> Result<T, DrawerError> {
+            let manifest = ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .call_method(self.component, method_name, args)
+                .build();
+            let receipt = self.ledger.execute_manifest(manifest, vec![]);
+            if !receipt.is_commit_success() {
+                return Err(DrawerError::Rejected(format!("{:?}", receipt.result)));
+            }
+
+            Ok(receipt.expect_commit_success().output(1))
+        }
+    }
+
+    impl<'a> LedgerClient for SimulatorClient<'a> {
+        fn pending_tickets(&mut self, limit: u32) -> Result<Vec<NonFungibleLocalId>, DrawerError> {
+            self.call("pending_tickets", manifest_args!(limit))
+        }
+
+        fn seed_commitment(&mut self) -> Result<Hash, DrawerError> {
+            self.call("seed_commitment", manifest_args!())
+        }
+
+        fn submit(&mut self, manifest: TransactionManifestV1) -> Result<(), DrawerError> {
+            let receipt = self.ledger.execute_manifest(manifest, vec![NonFungibleGlobalId::from_public_key(&self.public_key)]);
+            if !receipt.is_commit_success() {
+                return Err(DrawerError::Rejected(format!("{:?}", receipt.result)));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Ledger with a deployed component, the account holding the badge and the Shard, Shard Ticket and profile resources
+    struct Deployment {
+        ledger: DefaultLedgerSimulator,
+        public_key: Secp256k1PublicKey,
+        account: ComponentAddress,
+        component: ComponentAddress,
+        shard: ResourceAddress,
+        ticket: ResourceAddress,
+        profile: ResourceAddress,
+    }
+
+    impl Deployment {
+        fn execute(&mut self, manifest: TransactionManifestV1) {
+            self.ledger.execute_manifest(manifest, vec![NonFungibleGlobalId::from_public_key(&self.public_key)])
+                .expect_commit_success();
+        }
+
+        fn bond(&mut self, amount: u64, profile: bool) {
+            let (account, component, shard, profile_resource) = (self.account, self.component, self.shard, self.profile);
+            let mut builder = ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .withdraw_from_account(account, shard, Decimal::from(amount))
+                .take_all_from_worktop(shard, "deposit");
+            if profile {
+                builder = builder
+                    .create_proof_from_account_of_non_fungibles(account, profile_resource, [NonFungibleLocalId::integer(1)])
+                    .pop_from_auth_zone("profile");
+            }
+            let manifest = builder
+                .with_name_lookup(|builder, lookup| {
+                    let profile = profile.then(|| lookup.proof("profile"));
+                    builder.call_method(component, "bond", manifest_args!(lookup.bucket("deposit"), None::<ManifestBucket>, None::<ManifestProof>, profile, None::<Referrer>))
+                })
+                .deposit_batch(account)
+                .build();
+            self.execute(manifest);
+        }
+
+        fn ticket_data(&self, ticket_id: &NonFungibleLocalId) -> ShardTicket {
+            self.ledger.get_non_fungible_data(self.ticket, ticket_id.clone())
+        }
+
+        fn drain(&mut self) -> Vec<NonFungibleLocalId> {
+            let mut config = DrawerConfig::new(self.component, self.account, SHARDZ_BADGE);
+            config.batch_size = 10;
+            let client = SimulatorClient { ledger: &mut self.ledger, component: self.component, public_key: self.public_key };
+
+            Drawer::new(client, config, seed_chain()).drain().unwrap()
+        }
+    }
+
+    fn seed_chain() -> SeedChain {
+        SeedChain::new([3; 32], 100)
+    }
+
+    fn deploy() -> Deployment {
+        let simulator = ShardzSimulator::deploy(seed_chain().head());
+        let addresses = simulator.manifests.addresses.clone();
+
+        Deployment {
+            ledger: simulator.ledger,
+            public_key: simulator.public_key,
+            account: simulator.account,
+            component: addresses.component,
+            shard: addresses.shard,
+            ticket: addresses.ticket,
+            profile: addresses.profile,
+        }
+    }
+
+    #[test]
+    fn test_drain_against_blueprint() {
+        let mut deployment = deploy();
+        deployment.bond(25, false);
+
+        let ticket_ids = deployment.drain();
+        assert_eq!(ticket_ids.len(), 25);
+
+        // Tickets bonded without a profile are drawn with the published weights and the first revealed seed
+        let reveal = seed_chain().reveal(&seed_chain().head()).unwrap();
+        for ticket_id in ticket_ids.iter() {
+            let ticket_data = deployment.ticket_data(ticket_id);
+            let expected_tier = draw_tier(&default_tier_weights(), ticket_id, ticket_seed(&reveal, &ticket_data.bond_hash));
+            assert_eq!(ticket_data.shard_type, Some(expected_tier));
+        }
+        assert!(deployment.drain().is_empty());
+    }
+
+    #[test]
+    fn test_drain_applies_pity() {
+        let mut deployment = deploy();
+        let (account, component) = (deployment.account, deployment.component);
+
+        deployment.execute(ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(component, "create_profile", manifest_args!())
+            .create_proof_from_account_of_amount(account, SHARDZ_BADGE, dec!(1))
+            .call_method(component, "set_pity_config", manifest_args!(PityConfig { threshold: 0, tier: ShardType::Blue }))
+            .deposit_batch(account)
+            .build());
+        deployment.bond(5, true);
+
+        let ticket_ids = deployment.drain();
+        assert_eq!(ticket_ids.len(), 5);
+
+        // With a pity threshold of 0 every ticket of the profile lands on the pity tier or above, whatever the roll
+        for ticket_id in ticket_ids.iter() {
+            assert!(matches!(deployment.ticket_data(ticket_id).shard_type, Some(ShardType::Blue | ShardType::Scrypto | ShardType::Radix)));
+        }
+    }
+}
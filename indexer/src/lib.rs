@@ -0,0 +1,148 @@
+pub mod receipt;
+pub mod store;
+
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+use radix_shardz::shardz::{ShardType, ShardsDestroyedEvent, TicketDrawnEvent, TicketsMintedEvent, TicketsRecycledEvent, TicketsSwappedEvent, TicketsUnbondedEvent};
+use scrypto::prelude::*;
+
+use crate::receipt::{CommittedReceipt, ReceiptEvent};
+use crate::store::{IndexerError, Store, StoreTransaction, SHARD, TICKET};
+
+/// Account deposit and withdraw events, mirroring the SBOR layout of the native account events
+#[derive(ScryptoSbor, Debug)]
+enum AccountEvent {
+    Fungible(ResourceAddress, Decimal),
+    NonFungible(ResourceAddress, IndexSet<NonFungibleLocalId>),
+}
+
+/// Bech32 addresses of the indexed component and of its ticket and shard resources
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    pub component: String,
+    pub ticket: String,
+    pub nft: String,
+}
+
+pub struct Indexer {
+    config: IndexerConfig,
+    store: Store,
+    encoder: AddressBech32Encoder,
+}
+
+impl Indexer {
+    pub fn new(config: IndexerConfig, store: Store, network: &NetworkDefinition) -> Self {
+        Self {
+            config,
+            store,
+            encoder: AddressBech32Encoder::new(network),
+        }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Applies receipts in ledger order, receipts already indexed are skipped
+    pub fn index(&mut self, receipts: &[CommittedReceipt]) -> Result<(), IndexerError> {
+        for receipt in receipts {
+            if self.store.last_state_version()?.map_or(false, |version| receipt.state_version <= version) {
+                continue;
+            }
+
+            let transaction = self.store.begin()?;
+            for event in receipt.events.iter() {
+                apply(&self.config, &self.encoder, &transaction, receipt, event)?;
+            }
+            transaction.set_last_state_version(receipt.state_version)?;
+            transaction.commit()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn apply(config: &IndexerConfig, encoder: &AddressBech32Encoder, transaction: &StoreTransaction, receipt: &CommittedReceipt, event: &ReceiptEvent) -> Result<(), IndexerError> {
+    let version = receipt.state_version;
+    let timestamp = receipt.timestamp;
+
+    if event.emitter == config.component {
+        match event.name.as_str() {
+            "TicketsMintedEvent" => {
+                let event: TicketsMintedEvent = event.decode()?;
+                for ticket_id in event.ticket_ids {
+                    transaction.insert_ticket(&ticket_id.to_string(), event.shard_type.as_ref().map(tier_name), version)?;
+                    transaction.record(version, timestamp, "ticket_minted", TICKET, &ticket_id.to_string(), None, None)?;
+                }
+            }
+            "TicketDrawnEvent" => {
+                let event: TicketDrawnEvent = event.decode()?;
+                let ticket_id = event.ticket_id.to_string();
+                transaction.draw_ticket(&ticket_id, &tier_name(&event.shard_type), event.seed)?;
+                transaction.record(version, timestamp, "ticket_drawn", TICKET, &ticket_id, None, Some(&tier_name(&event.shard_type)))?;
+            }
+            "TicketsSwappedEvent" => {
+                let event: TicketsSwappedEvent = event.decode()?;
+                for (ticket_id, nft_id, shard_type) in event.swaps {
+                    transaction.swap_ticket(&ticket_id.to_string(), &nft_id.to_string(), &tier_name(&shard_type), version)?;
+                    transaction.record(version, timestamp, "shard_minted", SHARD, &nft_id.to_string(), None, Some(&ticket_id.to_string()))?;
+                }
+            }
+            "TicketsUnbondedEvent" => {
+                let event: TicketsUnbondedEvent = event.decode()?;
+                for ticket_id in event.ticket_ids {
+                    transaction.unbond_ticket(&ticket_id.to_string())?;
+                    transaction.record(version, timestamp, "ticket_unbonded", TICKET, &ticket_id.to_string(), None, None)?;
+                }
+            }
+            "TicketsRecycledEvent" => {
+                let event: TicketsRecycledEvent = event.decode()?;
+                for ticket_id in event.ticket_ids {
+                    transaction.recycle_ticket(&ticket_id.to_string())?;
+                    transaction.record(version, timestamp, "ticket_recycled", TICKET, &ticket_id.to_string(), None, None)?;
+                }
+            }
+            "ShardsDestroyedEvent" => {
+                let event: ShardsDestroyedEvent = event.decode()?;
+                for nft_id in event.nft_ids {
+                    transaction.destroy_shard(&nft_id.to_string())?;
+                    transaction.record(version, timestamp, "shard_destroyed", SHARD, &nft_id.to_string(), None, None)?;
+                }
+            }
+            _ => {}
+        }
+    } else if event.name == "DepositEvent" || event.name == "WithdrawEvent" {
+        // Ownership is tracked from the deposits and withdrawals of accounts, events of the same name emitted by
+        // vaults or other components have another layout and are skipped
+        let Ok(AccountEvent::NonFungible(resource_address, ids)) = event.decode::<AccountEvent>() else {
+            return Ok(());
+        };
+        let resource = encoder.encode(resource_address.as_node_id().as_bytes())
+            .map_err(|error| IndexerError::Decode(format!("{:?}", error)))?;
+        let deposit = event.name == "DepositEvent";
+        let owner = if deposit { Some(event.emitter.as_str()) } else { None };
+        let kind = if deposit { "deposit" } else { "withdraw" };
+
+        let item = if resource == config.ticket {
+            TICKET
+        } else if resource == config.nft {
+            SHARD
+        } else {
+            return Ok(());
+        };
+
+        for id in ids {
+            let id = id.to_string();
+            transaction.set_owner(item, &id, owner)?;
+            transaction.record(version, timestamp, kind, item, &id, Some(&event.emitter), None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tier name stored in the database, custom tiers are stored as `Custom(<id>)`
+pub fn tier_name(shard_type: &ShardType) -> String {
+    format!("{:?}", shard_type)
+}
@@ -0,0 +1,49 @@
+use std::env;
+use std::path::Path;
+use std::process;
+
+use scrypto::prelude::*;
+use shardz_indexer::receipt::read_dump;
+use shardz_indexer::store::Store;
+use shardz_indexer::{Indexer, IndexerConfig};
+
+/// Indexes JSON dumps of receipts into a SQLite database:
+/// `shardz-indexer <network> <component> <ticket resource> <nft resource> <database> <dump.json>...`
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 6 {
+        eprintln!("Usage: shardz-indexer <network> <component> <ticket resource> <nft resource> <database> <dump.json>...");
+        process::exit(1);
+    }
+
+    let network = match args[0].as_str() {
+        "simulator" => NetworkDefinition::simulator(),
+        "stokenet" => NetworkDefinition::stokenet(),
+        "mainnet" => NetworkDefinition::mainnet(),
+        other => {
+            eprintln!("Unsupported network {}", other);
+            process::exit(1);
+        }
+    };
+    let config = IndexerConfig {
+        component: args[1].clone(),
+        ticket: args[2].clone(),
+        nft: args[3].clone(),
+    };
+
+    let run = || -> Result<(), Box<dyn std::error::Error>> {
+        let mut indexer = Indexer::new(config, Store::open(Path::new(&args[4]))?, &network);
+        for dump in args[5..].iter() {
+            indexer.index(&read_dump(Path::new(dump))?)?;
+        }
+        for (tier, count) in indexer.store().tier_distribution()? {
+            println!("{}: {}", tier, count);
+        }
+        Ok(())
+    };
+
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
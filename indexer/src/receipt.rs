@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::Path;
+
+use scrypto::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::store::IndexerError;
+
+/// Committed transaction reduced to what the indexer needs, as stored in JSON dumps
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommittedReceipt {
+    pub state_version: u64,
+    /// Seconds since the unix epoch
+    pub timestamp: i64,
+    pub events: Vec<ReceiptEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReceiptEvent {
+    /// Bech32 address of the emitting component or account
+    pub emitter: String,
+    pub name: String,
+    /// Hex encoded SBOR payload of the event
+    pub data: String,
+}
+
+impl ReceiptEvent {
+    pub fn new<T: ScryptoEncode>(emitter: &str, name: &str, event: &T) -> Self {
+        Self {
+            emitter: emitter.to_string(),
+            name: name.to_string(),
+            data: hex::encode(scrypto_encode(event).unwrap()),
+        }
+    }
+
+    pub fn decode<T: ScryptoDecode>(&self) -> Result<T, IndexerError> {
+        let bytes = hex::decode(&self.data).map_err(|error| IndexerError::Decode(error.to_string()))?;
+        scrypto_decode(&bytes).map_err(|error| IndexerError::Decode(format!("{} {:?}", self.name, error)))
+    }
+}
+
+/// Reads a JSON dump holding an array of receipts
+pub fn read_dump(path: &Path) -> Result<Vec<CommittedReceipt>, IndexerError> {
+    let json = fs::read_to_string(path).map_err(|error| IndexerError::Decode(error.to_string()))?;
+    serde_json::from_str(&json).map_err(|error| IndexerError::Decode(error.to_string()))
+}
@@ -0,0 +1,22 @@
+use scrypto_test::prelude::*;
+
+use crate::receipt::{CommittedReceipt, ReceiptEvent};
+
+/// Converts the commit result of a scrypto-test ledger simulator transaction, keeping the events emitted by global
+/// components and accounts. Vaults emit their own `DepositEvent` and `WithdrawEvent`, which are left out.
+pub fn receipt_from_commit(state_version: u64, timestamp: i64, commit: &CommitResult, network: &NetworkDefinition) -> CommittedReceipt {
+    let encoder = AddressBech32Encoder::new(network);
+
+    let events = commit.application_events.iter()
+        .filter_map(|(EventTypeIdentifier(emitter, name), data)| match emitter {
+            Emitter::Method(node_id, ModuleId::Main) if node_id.is_global() => Some(ReceiptEvent {
+                emitter: encoder.encode(node_id.as_bytes()).ok()?,
+                name: name.clone(),
+                data: hex::encode(data),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    CommittedReceipt { state_version, timestamp, events }
+}
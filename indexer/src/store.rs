@@ -0,0 +1,285 @@
+use std::fmt;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+pub const TICKET: &str = "ticket";
+pub const SHARD: &str = "shard";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tickets (
+    id TEXT PRIMARY KEY,
+    shard_type TEXT,
+    seed INTEGER,
+    owner TEXT,
+    status TEXT NOT NULL,
+    minted_version INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS shards (
+    id TEXT PRIMARY KEY,
+    shard_type TEXT NOT NULL,
+    ticket_id TEXT NOT NULL,
+    owner TEXT,
+    destroyed INTEGER NOT NULL DEFAULT 0,
+    minted_version INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    state_version INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    item TEXT NOT NULL,
+    item_id TEXT NOT NULL,
+    account TEXT,
+    detail TEXT
+);
+CREATE INDEX IF NOT EXISTS history_item ON history (item, item_id);
+CREATE INDEX IF NOT EXISTS history_account ON history (account);
+CREATE TABLE IF NOT EXISTS cursor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    state_version INTEGER NOT NULL
+);
+";
+
+#[derive(Debug)]
+pub enum IndexerError {
+    Database(rusqlite::Error),
+    Decode(String),
+}
+
+impl fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexerError::Database(error) => write!(f, "database error: {}", error),
+            IndexerError::Decode(message) => write!(f, "decode error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for IndexerError {}
+
+impl From<rusqlite::Error> for IndexerError {
+    fn from(error: rusqlite::Error) -> Self {
+        IndexerError::Database(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketRow {
+    pub id: String,
+    pub shard_type: Option<String>,
+    pub seed: Option<u64>,
+    pub owner: Option<String>,
+    /// `bonded`, `drawn`, `swapped`, `unbonded` or `recycled`
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardRow {
+    pub id: String,
+    pub shard_type: String,
+    pub ticket_id: String,
+    pub owner: Option<String>,
+    pub destroyed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRow {
+    pub state_version: u64,
+    pub timestamp: i64,
+    pub kind: String,
+    pub item: String,
+    pub item_id: String,
+    pub account: Option<String>,
+    pub detail: Option<String>,
+}
+
+/// SQLite store of the indexed state, read by the frontend through the query methods
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, IndexerError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, IndexerError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, IndexerError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    pub fn begin(&mut self) -> Result<StoreTransaction<'_>, IndexerError> {
+        Ok(StoreTransaction { transaction: self.connection.transaction()? })
+    }
+
+    pub fn last_state_version(&self) -> Result<Option<u64>, IndexerError> {
+        Ok(self.connection
+            .query_row("SELECT state_version FROM cursor WHERE id = 0", [], |row| row.get(0))
+            .optional()?)
+    }
+
+    pub fn ticket(&self, id: &str) -> Result<Option<TicketRow>, IndexerError> {
+        Ok(self.connection
+            .query_row("SELECT id, shard_type, seed, owner, status FROM tickets WHERE id = ?1", [id], ticket_row)
+            .optional()?)
+    }
+
+    pub fn shard(&self, id: &str) -> Result<Option<ShardRow>, IndexerError> {
+        Ok(self.connection
+            .query_row("SELECT id, shard_type, ticket_id, owner, destroyed FROM shards WHERE id = ?1", [id], shard_row)
+            .optional()?)
+    }
+
+    pub fn tickets_of(&self, owner: &str) -> Result<Vec<TicketRow>, IndexerError> {
+        let mut statement = self.connection.prepare("SELECT id, shard_type, seed, owner, status FROM tickets WHERE owner = ?1 ORDER BY minted_version, id")?;
+        let rows = statement.query_map([owner], ticket_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn shards_of(&self, owner: &str) -> Result<Vec<ShardRow>, IndexerError> {
+        let mut statement = self.connection.prepare("SELECT id, shard_type, ticket_id, owner, destroyed FROM shards WHERE owner = ?1 AND destroyed = 0 ORDER BY minted_version, id")?;
+        let rows = statement.query_map([owner], shard_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Number of shards in circulation for each tier, rarest first
+    pub fn tier_distribution(&self) -> Result<Vec<(String, u64)>, IndexerError> {
+        let mut statement = self.connection.prepare("SELECT shard_type, COUNT(*) FROM shards WHERE destroyed = 0 GROUP BY shard_type ORDER BY COUNT(*), shard_type")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Latest events of a ticket or a shard, newest first
+    pub fn history(&self, item: &str, item_id: &str, limit: u32) -> Result<Vec<HistoryRow>, IndexerError> {
+        let mut statement = self.connection.prepare(
+            "SELECT state_version, timestamp, kind, item, item_id, account, detail FROM history
+             WHERE item = ?1 AND item_id = ?2 ORDER BY state_version DESC, rowid DESC LIMIT ?3")?;
+        let rows = statement.query_map(params![item, item_id, limit], history_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Latest deposits and withdrawals of an account, newest first
+    pub fn player_activity(&self, account: &str, limit: u32) -> Result<Vec<HistoryRow>, IndexerError> {
+        let mut statement = self.connection.prepare(
+            "SELECT state_version, timestamp, kind, item, item_id, account, detail FROM history
+             WHERE account = ?1 ORDER BY state_version DESC, rowid DESC LIMIT ?2")?;
+        let rows = statement.query_map(params![account, limit], history_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
+
+/// Writes of a single receipt, applied atomically on commit
+pub struct StoreTransaction<'a> {
+    transaction: Transaction<'a>,
+}
+
+impl<'a> StoreTransaction<'a> {
+    pub fn commit(self) -> Result<(), IndexerError> {
+        Ok(self.transaction.commit()?)
+    }
+
+    pub fn set_last_state_version(&self, state_version: u64) -> Result<(), IndexerError> {
+        self.transaction.execute(
+            "INSERT INTO cursor (id, state_version) VALUES (0, ?1) ON CONFLICT (id) DO UPDATE SET state_version = ?1",
+            [state_version],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_ticket(&self, id: &str, shard_type: Option<String>, version: u64) -> Result<(), IndexerError> {
+        let status = if shard_type.is_some() { "drawn" } else { "bonded" };
+        self.transaction.execute(
+            "INSERT INTO tickets (id, shard_type, status, minted_version) VALUES (?1, ?2, ?3, ?4)",
+            params![id, shard_type, status, version],
+        )?;
+        Ok(())
+    }
+
+    pub fn draw_ticket(&self, id: &str, shard_type: &str, seed: u64) -> Result<(), IndexerError> {
+        // Seeds are stored as their two's complement since SQLite integers are signed
+        self.transaction.execute(
+            "UPDATE tickets SET shard_type = ?2, seed = ?3, status = 'drawn' WHERE id = ?1",
+            params![id, shard_type, seed as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn swap_ticket(&self, ticket_id: &str, nft_id: &str, shard_type: &str, version: u64) -> Result<(), IndexerError> {
+        self.transaction.execute("UPDATE tickets SET status = 'swapped', owner = NULL WHERE id = ?1", [ticket_id])?;
+        self.transaction.execute(
+            "INSERT INTO shards (id, shard_type, ticket_id, minted_version) VALUES (?1, ?2, ?3, ?4)",
+            params![nft_id, shard_type, ticket_id, version],
+        )?;
+        Ok(())
+    }
+
+    pub fn unbond_ticket(&self, id: &str) -> Result<(), IndexerError> {
+        self.transaction.execute("UPDATE tickets SET status = 'unbonded', owner = NULL WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn recycle_ticket(&self, id: &str) -> Result<(), IndexerError> {
+        self.transaction.execute("UPDATE tickets SET status = 'recycled', owner = NULL WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn destroy_shard(&self, id: &str) -> Result<(), IndexerError> {
+        self.transaction.execute("UPDATE shards SET destroyed = 1, owner = NULL WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn set_owner(&self, item: &str, id: &str, owner: Option<&str>) -> Result<(), IndexerError> {
+        let statement = if item == TICKET {
+            "UPDATE tickets SET owner = ?2 WHERE id = ?1"
+        } else {
+            "UPDATE shards SET owner = ?2 WHERE id = ?1"
+        };
+        self.transaction.execute(statement, params![id, owner])?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(&self, state_version: u64, timestamp: i64, kind: &str, item: &str, item_id: &str, account: Option<&str>, detail: Option<&str>) -> Result<(), IndexerError> {
+        self.transaction.execute(
+            "INSERT INTO history (state_version, timestamp, kind, item, item_id, account, detail) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![state_version, timestamp, kind, item, item_id, account, detail],
+        )?;
+        Ok(())
+    }
+}
+
+fn ticket_row(row: &rusqlite::Row) -> rusqlite::Result<TicketRow> {
+    Ok(TicketRow {
+        id: row.get(0)?,
+        shard_type: row.get(1)?,
+        seed: row.get::<_, Option<i64>>(2)?.map(|seed| seed as u64),
+        owner: row.get(3)?,
+        status: row.get(4)?,
+    })
+}
+
+fn shard_row(row: &rusqlite::Row) -> rusqlite::Result<ShardRow> {
+    Ok(ShardRow {
+        id: row.get(0)?,
+        shard_type: row.get(1)?,
+        ticket_id: row.get(2)?,
+        owner: row.get(3)?,
+        destroyed: row.get(4)?,
+    })
+}
+
+fn history_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryRow> {
+    Ok(HistoryRow {
+        state_version: row.get(0)?,
+        timestamp: row.get(1)?,
+        kind: row.get(2)?,
+        item: row.get(3)?,
+        item_id: row.get(4)?,
+        account: row.get(5)?,
+        detail: row.get(6)?,
+    })
+}
@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod indexer_tests {
+    use radix_shardz::shardz::{ShardType, ShardsDestroyedEvent, TicketDrawnEvent, TicketsMintedEvent, TicketsSwappedEvent};
+    use scrypto::prelude::*;
+    use shardz_indexer::receipt::{CommittedReceipt, ReceiptEvent};
+    use shardz_indexer::store::{Store, SHARD};
+    use shardz_indexer::{Indexer, IndexerConfig};
+
+    const TICKET: ResourceAddress = ResourceAddress::new_or_panic([154; 30]);
+    const NFT: ResourceAddress = ResourceAddress::new_or_panic([154, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+    /// Same layout as the native account deposit and withdraw events
+    #[derive(ScryptoSbor)]
+    enum AccountEvent {
+        #[allow(dead_code)]
+        Fungible(ResourceAddress, Decimal),
+        NonFungible(ResourceAddress, IndexSet<NonFungibleLocalId>),
+    }
+
+    fn address(bytes: &[u8]) -> String {
+        AddressBech32Encoder::new(&NetworkDefinition::simulator()).encode(bytes).unwrap()
+    }
+
+    fn component() -> String {
+        address(&[192; 30])
+    }
+
+    fn account() -> String {
+        address(&[193; 30])
+    }
+
+    fn indexer() -> Indexer {
+        let config = IndexerConfig {
+            component: component(),
+            ticket: address(TICKET.as_node_id().as_bytes()),
+            nft: address(NFT.as_node_id().as_bytes()),
+        };
+        Indexer::new(config, Store::in_memory().unwrap(), &NetworkDefinition::simulator())
+    }
+
+    fn deposit(resource: ResourceAddress, ids: &[u64]) -> ReceiptEvent {
+        let ids = ids.iter().map(|id| NonFungibleLocalId::integer(*id)).collect();
+        ReceiptEvent::new(&account(), "DepositEvent", &AccountEvent::NonFungible(resource, ids))
+    }
+
+    fn receipts() -> Vec<CommittedReceipt> {
+        let ticket_ids = vec![NonFungibleLocalId::integer(1), NonFungibleLocalId::integer(2)];
+
+        vec![
+            CommittedReceipt {
+                state_version: 10,
+                timestamp: 1_700_000_000,
+                events: vec![
+                    ReceiptEvent::new(&component(), "TicketsMintedEvent", &TicketsMintedEvent { ticket_ids: ticket_ids.clone(), shard_type: None }),
+                    deposit(TICKET, &[1, 2]),
+                ],
+            },
+            CommittedReceipt {
+                state_version: 11,
+                timestamp: 1_700_000_060,
+                events: vec![
+                    ReceiptEvent::new(&component(), "TicketDrawnEvent", &TicketDrawnEvent { ticket_id: ticket_ids[0].clone(), shard_type: ShardType::Xian, seed: u64::MAX }),
+                    ReceiptEvent::new(&component(), "TicketDrawnEvent", &TicketDrawnEvent { ticket_id: ticket_ids[1].clone(), shard_type: ShardType::Clear, seed: 7 }),
+                ],
+            },
+            CommittedReceipt {
+                state_version: 12,
+                timestamp: 1_700_000_120,
+                events: vec![
+                    ReceiptEvent::new(&component(), "TicketsSwappedEvent", &TicketsSwappedEvent {
+                        swaps: vec![
+                            (ticket_ids[0].clone(), NonFungibleLocalId::integer(1), ShardType::Xian),
+                            (ticket_ids[1].clone(), NonFungibleLocalId::integer(2), ShardType::Clear),
+                        ],
+                    }),
+                    deposit(NFT, &[1, 2]),
+                ],
+            },
+            CommittedReceipt {
+                state_version: 13,
+                timestamp: 1_700_000_180,
+                events: vec![
+                    ReceiptEvent::new(&component(), "ShardsDestroyedEvent", &ShardsDestroyedEvent { nft_ids: vec![NonFungibleLocalId::integer(2)] }),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_index_ticket_lifecycle() {
+        let mut indexer = indexer();
+        indexer.index(&receipts()).unwrap();
+        let store = indexer.store();
+
+        let ticket = store.ticket("#1#").unwrap().unwrap();
+        assert_eq!(ticket.status, "swapped");
+        assert_eq!(ticket.shard_type.as_deref(), Some("Xian"));
+        assert_eq!(ticket.seed, Some(u64::MAX));
+
+        let shards = store.shards_of(&account()).unwrap();
+        assert_eq!(shards.len(), 1);
+        assert_eq!(shards[0].shard_type, "Xian");
+        assert!(store.shard("#2#").unwrap().unwrap().destroyed);
+
+        assert_eq!(store.tier_distribution().unwrap(), vec![("Xian".to_string(), 1)]);
+
+        let history = store.history(SHARD, "#2#", 10).unwrap();
+        assert_eq!(history.iter().map(|row| row.kind.as_str()).collect::<Vec<_>>(), vec!["shard_destroyed", "deposit", "shard_minted"]);
+        assert_eq!(store.player_activity(&account(), 10).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_receipts_are_indexed_once() {
+        let mut indexer = indexer();
+        let receipts = receipts();
+
+        indexer.index(&receipts[..2]).unwrap();
+        assert_eq!(indexer.store().last_state_version().unwrap(), Some(11));
+
+        // Replaying a dump that overlaps the indexed receipts only applies the new ones
+        indexer.index(&receipts).unwrap();
+        assert_eq!(indexer.store().last_state_version().unwrap(), Some(13));
+        assert_eq!(indexer.store().history(SHARD, "#1#", 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_foreign_deposit_events_are_skipped() {
+        let mut indexer = indexer();
+        let mut receipts = receipts();
+
+        // Fungible vaults emit a `DepositEvent` holding only the amount
+        receipts[0].events.push(ReceiptEvent::new(&account(), "DepositEvent", &dec!(5)));
+        indexer.index(&receipts).unwrap();
+
+        assert_eq!(indexer.store().shards_of(&account()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json_dump_round_trip() {
+        let json = serde_json::to_string(&receipts()).unwrap();
+        let decoded: Vec<CommittedReceipt> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, receipts());
+    }
+}
+
+#[cfg(all(test, feature = "simulator"))]
+mod simulator_tests {
+    use radix_shardz::shardz::Referrer;
+    use scrypto_test::prelude::*;
+    use shardz_indexer::simulator::receipt_from_commit;
+    use shardz_indexer::store::Store;
+    use shardz_indexer::{Indexer, IndexerConfig};
+
+    fn resource_named(ledger: &mut DefaultLedgerSimulator, commit: &CommitResult, name: &str) -> ResourceAddress {
+        *commit.new_resource_addresses().iter()
+            .find(|address| ledger.get_metadata((**address).into(), "name") == Some(MetadataValue::String(name.to_string())))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_index_simulator_receipt() {
+        let network = NetworkDefinition::simulator();
+        let encoder = AddressBech32Encoder::new(&network);
+        let mut ledger = LedgerSimulatorBuilder::new().build();
+        let (public_key, _, account) = ledger.new_allocated_account();
+        let package = ledger.compile_and_publish(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(package, "Shardz", "instantiate_shardz", manifest_args!(account, Hash([1; 32])))
+            .deposit_batch(account)
+            .build();
+        let receipt = ledger.execute_manifest(manifest, vec![]);
+        let commit = receipt.expect_commit_success().clone();
+        let component = commit.new_component_addresses()[0];
+        let shard = resource_named(&mut ledger, &commit, "Shard");
+        let ticket = resource_named(&mut ledger, &commit, "Shard Ticket");
+        let nft = resource_named(&mut ledger, &commit, "Shard NFT");
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, shard, dec!(3))
+            .take_all_from_worktop(shard, "deposit")
+            .with_name_lookup(|builder, lookup| {
+                builder.call_method(
+                    component,
+                    "bond",
+                    manifest_args!(lookup.bucket("deposit"), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>),
+                )
+            })
+            .deposit_batch(account)
+            .build();
+        let receipt = ledger.execute_manifest(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+        let commit = receipt.expect_commit_success();
+
+        let config = IndexerConfig {
+            component: encoder.encode(component.as_node_id().as_bytes()).unwrap(),
+            ticket: encoder.encode(ticket.as_node_id().as_bytes()).unwrap(),
+            nft: encoder.encode(nft.as_node_id().as_bytes()).unwrap(),
+        };
+        let mut indexer = Indexer::new(config, Store::in_memory().unwrap(), &network);
+        indexer.index(&[receipt_from_commit(1, 1_700_000_000, commit, &network)]).unwrap();
+
+        let owner = encoder.encode(account.as_node_id().as_bytes()).unwrap();
+        let tickets = indexer.store().tickets_of(&owner).unwrap();
+        assert_eq!(tickets.len(), 3);
+        assert!(tickets.iter().all(|ticket| ticket.status == "bonded" && ticket.shard_type.is_none()));
+    }
+}
@@ -0,0 +1,47 @@
+use std::env;
+use std::fs;
+
+use radix_transactions::prelude::*;
+use radix_shardz::shardz::SeedChain;
+use scrypto::prelude::*;
+use shardz_manifests::{instantiate_manifest, render, ShardzAddresses, ShardzManifests};
+
+/// Writes the `.rtm` files of the main Shardz methods to `rtms/` for the addresses of a simulator deployment:
+/// `cargo run -p shardz-manifests --example rtms -- <package> <account> <component> <shard> <ticket> <nft> <profile> <badge>`
+fn main() {
+    let network = NetworkDefinition::simulator();
+    let decoder = AddressBech32Decoder::new(&network);
+    let args: Vec<String> = env::args().skip(1).collect();
+    assert_eq!(args.len(), 8, "Usage: rtms <package> <account> <component> <shard> <ticket> <nft> <profile> <badge>");
+
+    let resource = |index: usize| ResourceAddress::try_from_bech32(&decoder, &args[index]).expect("Invalid resource address");
+    let package = PackageAddress::try_from_bech32(&decoder, &args[0]).expect("Invalid package address");
+    let account = ComponentAddress::try_from_bech32(&decoder, &args[1]).expect("Invalid account address");
+
+    let manifests = ShardzManifests::new(ShardzAddresses {
+        component: ComponentAddress::try_from_bech32(&decoder, &args[2]).expect("Invalid component address"),
+        shard: resource(3),
+        ticket: resource(4),
+        nft: resource(5),
+        profile: resource(6),
+        badge: resource(7),
+        fee_resource: XRD,
+    })
+    .with_fee(dec!(5000));
+
+    // Fixed seed chain, only meant for local testing
+    let seed_chain = SeedChain::new([1; 32], 10);
+    let ids = vec![NonFungibleLocalId::integer(1), NonFungibleLocalId::integer(2)];
+    let rtms = [
+        ("instantiate", instantiate_manifest(package, account, seed_chain.head(), account, Some(dec!(5000)))),
+        ("bond", manifests.bond_manifest(account, 2, None, None, None, None)),
+        ("draw_batch", manifests.draw_manifest(account, ids.clone(), seed_chain.reveal(&seed_chain.head()))),
+        ("swap_tickets", manifests.swap_tickets_manifest(account, ids.clone(), None)),
+        ("destroy", manifests.destroy_manifest(account, ids, None)),
+    ];
+
+    fs::create_dir_all("rtms").unwrap();
+    for (name, manifest) in rtms {
+        fs::write(format!("rtms/{}.rtm", name), render(&manifest, &network).unwrap()).unwrap();
+    }
+}
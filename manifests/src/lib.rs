@@ -0,0 +1,255 @@
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+use radix_shardz::shardz::Referrer;
+use radix_transactions::manifest::{decompile, DecompileError};
+use radix_transactions::prelude::*;
+use scrypto::prelude::*;
+
+/// Addresses of a deployed Shardz component and of its resources
+#[derive(Debug, Clone)]
+pub struct ShardzAddresses {
+    pub component: ComponentAddress,
+    pub shard: ResourceAddress,
+    pub ticket: ResourceAddress,
+    pub nft: ResourceAddress,
+    pub profile: ResourceAddress,
+    pub badge: ResourceAddress,
+    /// Resource the draw fee is paid with
+    pub fee_resource: ResourceAddress,
+}
+
+/// Fee exempt badge proven by `bond_manifest`, `Fungible` proves an amount of the resource held by the account
+#[derive(Debug, Clone)]
+pub enum BadgeProof {
+    Fungible(ResourceAddress, Decimal),
+    NonFungible(NonFungibleGlobalId),
+}
+
+/// Builds the manifests of the Shardz methods for a deployed component.
+/// Every manifest locks `fee` from the account when it is set, otherwise the wallet is expected to add the fee lock.
+#[derive(Debug, Clone)]
+pub struct ShardzManifests {
+    pub addresses: ShardzAddresses,
+    pub fee: Option<Decimal>,
+}
+
+impl ShardzManifests {
+    pub fn new(addresses: ShardzAddresses) -> Self {
+        Self { addresses, fee: None }
+    }
+
+    pub fn with_fee(mut self, fee: Decimal) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    fn builder(&self, account: ComponentAddress) -> ManifestBuilder {
+        match self.fee {
+            Some(fee) => ManifestBuilder::new().lock_fee(account, fee),
+            None => ManifestBuilder::new(),
+        }
+    }
+
+    /// Creates a proof of the player profile when one is given
+    fn with_profile(&self, builder: ManifestBuilder, account: ComponentAddress, profile: &Option<NonFungibleLocalId>) -> ManifestBuilder {
+        match profile {
+            Some(profile_id) => builder
+                .create_proof_from_account_of_non_fungibles(account, self.addresses.profile, [profile_id.clone()])
+                .pop_from_auth_zone("profile"),
+            None => builder,
+        }
+    }
+
+    /// Bonds `amount` SHARD, paying up to `payment` of the fee resource for the draw fee when a fee is due.
+    /// A fee exempt `badge` held by the account is proven instead of paying.
+    pub fn bond_manifest(&self, account: ComponentAddress, amount: u64, payment: Option<Decimal>, badge: Option<BadgeProof>, profile: Option<NonFungibleLocalId>, referrer: Option<Referrer>) -> TransactionManifestV1 {
+        let builder = self.builder(account)
+            .withdraw_from_account(account, self.addresses.shard, Decimal::from(amount))
+            .take_all_from_worktop(self.addresses.shard, "deposit");
+        let builder = match payment {
+            Some(payment) => builder
+                .withdraw_from_account(account, self.addresses.fee_resource, payment)
+                .take_all_from_worktop(self.addresses.fee_resource, "payment"),
+            None => builder,
+        };
+        let builder = match &badge {
+            Some(BadgeProof::Fungible(resource_address, amount)) => builder
+                .create_proof_from_account_of_amount(account, *resource_address, *amount)
+                .pop_from_auth_zone("badge"),
+            Some(BadgeProof::NonFungible(badge)) => builder
+                .create_proof_from_account_of_non_fungibles(account, badge.resource_address(), [badge.local_id().clone()])
+                .pop_from_auth_zone("badge"),
+            None => builder,
+        };
+
+        self.with_profile(builder, account, &profile)
+            .with_name_lookup(|builder, lookup| {
+                let payment = payment.map(|_| lookup.bucket("payment"));
+                let badge = badge.map(|_| lookup.proof("badge"));
+                let profile = profile.map(|_| lookup.proof("profile"));
+                builder.call_method(
+                    self.addresses.component,
+                    "bond",
+                    manifest_args!(lookup.bucket("deposit"), payment, badge, profile, referrer),
+                )
+            })
+            .deposit_batch(account)
+            .build()
+    }
+
+    pub fn swap_tickets_manifest(&self, account: ComponentAddress, ids: Vec<NonFungibleLocalId>, profile: Option<NonFungibleLocalId>) -> TransactionManifestV1 {
+        self.call_with_non_fungibles(account, self.addresses.ticket, ids, "swap_tickets", profile)
+    }
+
+    pub fn destroy_manifest(&self, account: ComponentAddress, ids: Vec<NonFungibleLocalId>, profile: Option<NonFungibleLocalId>) -> TransactionManifestV1 {
+        self.call_with_non_fungibles(account, self.addresses.nft, ids, "destroy", profile)
+    }
+
+    pub fn unbond_manifest(&self, account: ComponentAddress, ids: Vec<NonFungibleLocalId>) -> TransactionManifestV1 {
+        self.builder(account)
+            .withdraw_non_fungibles_from_account(account, self.addresses.ticket, ids)
+            .take_all_from_worktop(self.addresses.ticket, "tickets")
+            .with_name_lookup(|builder, lookup| {
+                builder.call_method(self.addresses.component, "unbond", manifest_args!(lookup.bucket("tickets")))
+            })
+            .deposit_batch(account)
+            .build()
+    }
+
+    pub fn create_profile_manifest(&self, account: ComponentAddress) -> TransactionManifestV1 {
+        self.builder(account)
+            .call_method(self.addresses.component, "create_profile", manifest_args!())
+            .deposit_batch(account)
+            .build()
+    }
+
+    /// Draws tickets, revealing the seed of the current commitment if given, `account` must hold the drawer badge
+    pub fn draw_manifest(&self, account: ComponentAddress, ticket_ids: Vec<NonFungibleLocalId>, reveal: Option<Hash>) -> TransactionManifestV1 {
+        self.admin(account).draw_manifest(ticket_ids, reveal)
+    }
+
+    pub fn advance_pending_queue_manifest(&self, account: ComponentAddress, limit: u32) -> TransactionManifestV1 {
+        self.builder(account)
+            .call_method(self.addresses.component, "advance_pending_queue", manifest_args!(limit))
+            .build()
+    }
+
+    /// Calls a method restricted to the owner or the drawer role with a proof of the badge held by `account`
+    pub fn badge_call_manifest(&self, account: ComponentAddress, method_name: &str, args: impl ResolvableArguments) -> TransactionManifestV1 {
+        self.admin(account).badge_call_manifest(method_name, args)
+    }
+
+    pub fn admin(&self, account: ComponentAddress) -> AdminManifests {
+        AdminManifests {
+            component: self.addresses.component,
+            badge: self.addresses.badge,
+            account,
+            fee: self.fee,
+        }
+    }
+
+    fn call_with_non_fungibles(&self, account: ComponentAddress, resource: ResourceAddress, ids: Vec<NonFungibleLocalId>, method_name: &str, profile: Option<NonFungibleLocalId>) -> TransactionManifestV1 {
+        let builder = self.builder(account)
+            .withdraw_non_fungibles_from_account(account, resource, ids)
+            .take_all_from_worktop(resource, "bucket");
+
+        self.with_profile(builder, account, &profile)
+            .with_name_lookup(|builder, lookup| {
+                let profile = profile.map(|_| lookup.proof("profile"));
+                builder.call_method(self.addresses.component, method_name, manifest_args!(lookup.bucket("bucket"), profile))
+            })
+            .deposit_batch(account)
+            .build()
+    }
+}
+
+/// Builds the manifests of the owner and drawer operations, which only need the component and the badge held by `account`
+#[derive(Debug, Clone)]
+pub struct AdminManifests {
+    pub component: ComponentAddress,
+    pub badge: ResourceAddress,
+    pub account: ComponentAddress,
+    pub fee: Option<Decimal>,
+}
+
+impl AdminManifests {
+    fn builder(&self) -> ManifestBuilder {
+        let builder = match self.fee {
+            Some(fee) => ManifestBuilder::new().lock_fee(self.account, fee),
+            None => ManifestBuilder::new(),
+        };
+
+        builder.create_proof_from_account_of_amount(self.account, self.badge, dec!(1))
+    }
+
+    pub fn badge_call_manifest(&self, method_name: &str, args: impl ResolvableArguments) -> TransactionManifestV1 {
+        self.builder()
+            .call_method(self.component, method_name, args)
+            .deposit_batch(self.account)
+            .build()
+    }
+
+    pub fn draw_manifest(&self, ticket_ids: Vec<NonFungibleLocalId>, reveal: Option<Hash>) -> TransactionManifestV1 {
+        self.badge_call_manifest("draw_batch", manifest_args!(ticket_ids, reveal))
+    }
+
+    /// Starts a new seed chain of the drawer
+    pub fn commit_seed_manifest(&self, commitment: Hash) -> TransactionManifestV1 {
+        self.badge_call_manifest("commit_seed", manifest_args!(commitment))
+    }
+
+    pub fn set_paused_manifest(&self, paused: bool) -> TransactionManifestV1 {
+        self.badge_call_manifest("set_paused", manifest_args!(paused))
+    }
+
+    pub fn withdraw_treasury_manifest(&self, resource_address: ResourceAddress, amount: Decimal) -> TransactionManifestV1 {
+        self.badge_call_manifest("withdraw_treasury", manifest_args!(resource_address, amount))
+    }
+
+    pub fn set_royalty_manifest(&self, method_name: &str, amount: RoyaltyAmount) -> TransactionManifestV1 {
+        self.builder()
+            .set_component_royalty(self.component, method_name, amount)
+            .build()
+    }
+
+    pub fn claim_royalties_manifest(&self) -> TransactionManifestV1 {
+        self.builder()
+            .claim_component_royalties(self.component)
+            .deposit_batch(self.account)
+            .build()
+    }
+
+    /// Only succeeds with the owner badge, which is the metadata setter of the component
+    pub fn set_metadata_manifest(&self, key: &str, value: String) -> TransactionManifestV1 {
+        self.builder()
+            .set_metadata(self.component, key, value)
+            .build()
+    }
+}
+
+/// Reads the component statistics, to be previewed rather than submitted
+pub fn stats_manifest(component: ComponentAddress) -> TransactionManifestV1 {
+    ManifestBuilder::new()
+        .call_method(component, "stats", manifest_args!())
+        .build()
+}
+
+/// Instantiates a component from a published package, the owner badge must already be held by `account`.
+/// `seed_commitment` is the head of the drawer's seed chain.
+pub fn instantiate_manifest(package: PackageAddress, dapp_definition: ComponentAddress, seed_commitment: Hash, account: ComponentAddress, fee: Option<Decimal>) -> TransactionManifestV1 {
+    let builder = match fee {
+        Some(fee) => ManifestBuilder::new().lock_fee(account, fee),
+        None => ManifestBuilder::new(),
+    };
+
+    builder
+        .call_function(package, "Shardz", "instantiate_shardz", manifest_args!(dapp_definition, seed_commitment))
+        .deposit_batch(account)
+        .build()
+}
+
+/// Renders a manifest as `.rtm` text for the given network
+pub fn render(manifest: &TransactionManifestV1, network: &NetworkDefinition) -> Result<String, DecompileError> {
+    decompile(&manifest.instructions, network)
+}
@@ -0,0 +1,102 @@
+use radix_transactions::model::PreAllocatedAddress;
+use scrypto_test::prelude::*;
+
+use crate::{instantiate_manifest, ShardzAddresses, ShardzManifests};
+
+/// Address of the owner and drawer badge hard coded in the blueprint
+pub const SHARDZ_BADGE: ResourceAddress = ResourceAddress::new_or_panic([93, 234, 158, 5, 11, 143, 100, 156, 203, 137, 140, 82, 189, 231, 139, 42, 183, 255, 29, 40, 228, 152, 189, 32, 191, 126, 184, 201, 245, 89]);
+
+/// Ledger simulator with a deployed component, `account` holds the badge and the initial supply of SHARD.
+/// `manifests` lock their fee from the account and pay the draw fee with XRD until `fee_resource` is changed.
+pub struct ShardzSimulator {
+    pub ledger: DefaultLedgerSimulator,
+    pub public_key: Secp256k1PublicKey,
+    pub account: ComponentAddress,
+    pub manifests: ShardzManifests,
+}
+
+impl ShardzSimulator {
+    /// Publishes the blueprint and instantiates it with `seed_commitment` as the head of the drawer's seed chain
+    pub fn deploy(seed_commitment: Hash) -> Self {
+        let mut ledger = LedgerSimulatorBuilder::new().build();
+        let (public_key, _, account) = ledger.new_allocated_account();
+        let proofs = btreeset!(NonFungibleGlobalId::from_public_key(&public_key));
+
+        // The badge can only be created at its hard coded address by a system transaction
+        let create_badge = InstructionV1::CallFunction {
+            package_address: RESOURCE_PACKAGE.into(),
+            blueprint_name: FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT.to_string(),
+            function_name: FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&FungibleResourceManagerCreateWithInitialSupplyManifestInput {
+                owner_role: OwnerRole::None,
+                track_total_supply: true,
+                divisibility: 0,
+                resource_roles: FungibleResourceRoles::default(),
+                metadata: ModuleConfig::default(),
+                initial_supply: dec!(1),
+                address_reservation: Some(ManifestAddressReservation(0)),
+            }),
+        };
+        let deposit_badge = InstructionV1::CallMethod {
+            address: account.into(),
+            method_name: ACCOUNT_DEPOSIT_BATCH_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&(ManifestExpression::EntireWorktop,)),
+        };
+        let pre_allocated_badge = PreAllocatedAddress {
+            blueprint_id: BlueprintId::new(&RESOURCE_PACKAGE, FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT),
+            address: SHARDZ_BADGE.into(),
+        };
+        ledger.execute_system_transaction_with_preallocated_addresses(vec![create_badge, deposit_badge], vec![pre_allocated_badge], proofs)
+            .expect_commit_success();
+
+        let package = ledger.compile_and_publish(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+        let fee = dec!(50);
+        let receipt = ledger.execute_manifest(
+            instantiate_manifest(package, account, seed_commitment, account, Some(fee)),
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        );
+        let commit = receipt.expect_commit_success().clone();
+
+        let addresses = ShardzAddresses {
+            component: commit.new_component_addresses()[0],
+            shard: resource_named(&mut ledger, &commit, "Shard"),
+            ticket: resource_named(&mut ledger, &commit, "Shard Ticket"),
+            nft: resource_named(&mut ledger, &commit, "Shard NFT"),
+            profile: resource_named(&mut ledger, &commit, "Shardz Player Profile"),
+            badge: SHARDZ_BADGE,
+            fee_resource: XRD,
+        };
+
+        Self {
+            ledger,
+            public_key,
+            account,
+            manifests: ShardzManifests::new(addresses).with_fee(fee),
+        }
+    }
+
+    /// Executes a manifest signed by the key of `account`
+    pub fn execute(&mut self, manifest: TransactionManifestV1) -> TransactionReceipt {
+        self.ledger.execute_manifest(manifest, vec![NonFungibleGlobalId::from_public_key(&self.public_key)])
+    }
+
+    /// Executes a manifest built without a fee lock, such as `stats_manifest`, locking the fee from the faucet
+    pub fn execute_with_faucet_fee(&mut self, mut manifest: TransactionManifestV1) -> TransactionReceipt {
+        manifest.instructions.insert(0, InstructionV1::CallMethod {
+            address: FAUCET.into(),
+            method_name: "lock_fee".to_string(),
+            args: to_manifest_value_and_unwrap!(&(dec!(5000),)),
+        });
+        self.execute(manifest)
+    }
+
+    pub fn balance(&mut self, resource_address: ResourceAddress) -> Decimal {
+        self.ledger.get_component_balance(self.account, resource_address)
+    }
+}
+
+fn resource_named(ledger: &mut DefaultLedgerSimulator, commit: &CommitResult, name: &str) -> ResourceAddress {
+    *commit.new_resource_addresses().iter()
+        .find(|address| ledger.get_metadata((**address).into(), "name") == Some(MetadataValue::String(name.to_string())))
+        .unwrap()
+}
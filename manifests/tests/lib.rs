@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod manifests_tests {
+    use radix_shardz::shardz::Referrer;
+    use radix_transactions::manifest::{compile, BlobProvider};
+    use radix_transactions::prelude::*;
+    use scrypto::prelude::*;
+    use shardz_manifests::{instantiate_manifest, render, stats_manifest, BadgeProof, ShardzAddresses, ShardzManifests};
+
+    const ACCOUNT: ComponentAddress = ComponentAddress::new_or_panic([193; 30]);
+
+    fn manifests() -> ShardzManifests {
+        ShardzManifests::new(ShardzAddresses {
+            component: ComponentAddress::new_or_panic([192; 30]),
+            shard: ResourceAddress::new_or_panic([93; 30]),
+            ticket: ResourceAddress::new_or_panic([154; 30]),
+            nft: ResourceAddress::new_or_panic([154; 30]),
+            profile: ResourceAddress::new_or_panic([154; 30]),
+            badge: ResourceAddress::new_or_panic([93; 30]),
+            fee_resource: XRD,
+        })
+        .with_fee(dec!(10))
+    }
+
+    /// Renders a manifest and compiles it back, so that the `.rtm` text is a faithful copy of the manifest
+    fn round_trip(manifest: TransactionManifestV1) -> String {
+        let network = NetworkDefinition::simulator();
+        let rendered = render(&manifest, &network).unwrap();
+        let compiled = compile(&rendered, &network, BlobProvider::new()).unwrap();
+        assert_eq!(compiled.instructions, manifest.instructions);
+        rendered
+    }
+
+    #[test]
+    fn test_bond_manifest() {
+        let rendered = round_trip(manifests().bond_manifest(ACCOUNT, 3, None, None, None, None));
+        assert!(rendered.contains("\"lock_fee\""));
+        assert!(rendered.contains("\"bond\""));
+        assert!(rendered.contains("Bucket(\"deposit\")"));
+        assert!(!rendered.contains("Bucket(\"payment\")"));
+        assert!(!rendered.contains("Proof(\"badge\")"));
+
+        let referrer = Referrer::Profile(NonFungibleLocalId::integer(2));
+        let rendered = round_trip(manifests().bond_manifest(ACCOUNT, 3, Some(dec!(5)), None, Some(NonFungibleLocalId::integer(1)), Some(referrer)));
+        assert!(rendered.contains("Bucket(\"payment\")"));
+        assert!(rendered.contains("Proof(\"profile\")"));
+
+        let badge = NonFungibleGlobalId::new(ResourceAddress::new_or_panic([154; 30]), NonFungibleLocalId::integer(7));
+        let rendered = round_trip(manifests().bond_manifest(ACCOUNT, 3, None, Some(BadgeProof::NonFungible(badge)), None, None));
+        assert!(rendered.contains("\"create_proof_from_account_of_non_fungibles\""));
+        assert!(rendered.contains("Proof(\"badge\")"));
+        assert!(!rendered.contains("Bucket(\"payment\")"));
+
+        let badge = BadgeProof::Fungible(ResourceAddress::new_or_panic([93; 30]), dec!(1));
+        let rendered = round_trip(manifests().bond_manifest(ACCOUNT, 3, None, Some(badge), None, None));
+        assert!(rendered.contains("\"create_proof_from_account_of_amount\""));
+        assert!(rendered.contains("Proof(\"badge\")"));
+    }
+
+    #[test]
+    fn test_ticket_and_shard_manifests() {
+        let ids = vec![NonFungibleLocalId::integer(1), NonFungibleLocalId::integer(2)];
+
+        assert!(round_trip(manifests().swap_tickets_manifest(ACCOUNT, ids.clone(), None)).contains("\"swap_tickets\""));
+        assert!(round_trip(manifests().destroy_manifest(ACCOUNT, ids.clone(), None)).contains("\"destroy\""));
+        assert!(round_trip(manifests().unbond_manifest(ACCOUNT, ids)).contains("\"unbond\""));
+
+        let rendered = round_trip(manifests().draw_manifest(ACCOUNT, vec![NonFungibleLocalId::integer(1)], Some(Hash([42; 32]))));
+        assert!(rendered.contains("\"create_proof_of_amount\""));
+        assert!(rendered.contains("\"draw_batch\""));
+    }
+
+    #[test]
+    fn test_instantiate_manifest() {
+        let package = PackageAddress::new_or_panic([13; 30]);
+        let rendered = round_trip(instantiate_manifest(package, ACCOUNT, Hash([42; 32]), ACCOUNT, None));
+        assert!(rendered.contains("\"instantiate_shardz\""));
+        assert!(!rendered.contains("\"lock_fee\""));
+    }
+
+    #[test]
+    fn test_admin_manifests() {
+        let admin = manifests().admin(ACCOUNT);
+
+        assert!(round_trip(admin.set_paused_manifest(true)).contains("\"set_paused\""));
+        assert!(round_trip(admin.withdraw_treasury_manifest(XRD, dec!(100))).contains("\"withdraw_treasury\""));
+        assert!(round_trip(admin.set_royalty_manifest("bond", RoyaltyAmount::Xrd(dec!(2)))).contains("\"bond\""));
+        round_trip(admin.claim_royalties_manifest());
+        assert!(round_trip(admin.set_metadata_manifest("name", "Shardz".to_string())).contains("\"Shardz\""));
+        assert!(round_trip(stats_manifest(admin.component)).contains("\"stats\""));
+    }
+}
+
+#[cfg(all(test, feature = "simulator"))]
+mod simulator_tests {
+    use radix_shardz::shardz::{SeedChain, ShardTicket, ShardzStats};
+    use scrypto_test::prelude::*;
+    use shardz_manifests::simulator::ShardzSimulator;
+    use shardz_manifests::{stats_manifest, BadgeProof};
+
+    fn seed_chain() -> SeedChain {
+        SeedChain::new([1; 32], 10)
+    }
+
+    fn ids(range: std::ops::RangeInclusive<u64>) -> Vec<NonFungibleLocalId> {
+        range.map(NonFungibleLocalId::integer).collect()
+    }
+
+    #[test]
+    fn test_player_manifests() {
+        let mut simulator = ShardzSimulator::deploy(seed_chain().head());
+        let (manifests, account) = (simulator.manifests.clone(), simulator.account);
+        let profile = Some(NonFungibleLocalId::integer(1));
+
+        simulator.execute(manifests.create_profile_manifest(account)).expect_commit_success();
+        assert_eq!(simulator.balance(manifests.addresses.profile), dec!(1));
+
+        simulator.execute(manifests.bond_manifest(account, 3, None, None, profile.clone(), None)).expect_commit_success();
+        assert_eq!(simulator.balance(manifests.addresses.ticket), dec!(3));
+        assert_eq!(simulator.balance(manifests.addresses.shard), dec!(997));
+
+        let reveal = seed_chain().reveal(&seed_chain().head());
+        simulator.execute(manifests.draw_manifest(account, ids(1..=3), reveal)).expect_commit_success();
+        let ticket: ShardTicket = simulator.ledger.get_non_fungible_data(manifests.addresses.ticket, NonFungibleLocalId::integer(1));
+        assert!(ticket.shard_type.is_some());
+
+        simulator.execute(manifests.swap_tickets_manifest(account, ids(1..=3), profile.clone())).expect_commit_success();
+        assert_eq!(simulator.balance(manifests.addresses.ticket), dec!(0));
+        assert_eq!(simulator.balance(manifests.addresses.nft), dec!(3));
+
+        simulator.execute(manifests.badge_call_manifest(account, "set_reroll_cooldown", manifest_args!(0u32))).expect_commit_success();
+        simulator.execute(manifests.destroy_manifest(account, ids(1..=1), profile)).expect_commit_success();
+        assert_eq!(simulator.balance(manifests.addresses.nft), dec!(2));
+        assert_eq!(simulator.balance(manifests.addresses.shard), dec!(998));
+
+        // Tickets bonded after the reveal are unbonded before their own seed is revealed
+        simulator.execute(manifests.badge_call_manifest(account, "set_unbond_timeout", manifest_args!(0u32))).expect_commit_success();
+        simulator.execute(manifests.bond_manifest(account, 2, None, None, None, None)).expect_commit_success();
+        simulator.execute(manifests.unbond_manifest(account, ids(4..=5))).expect_commit_success();
+        assert_eq!(simulator.balance(manifests.addresses.ticket), dec!(0));
+        assert_eq!(simulator.balance(manifests.addresses.shard), dec!(998));
+
+        simulator.execute(manifests.advance_pending_queue_manifest(account, 10)).expect_commit_success();
+        let receipt = simulator.execute_with_faucet_fee(stats_manifest(manifests.addresses.component));
+        let stats: ShardzStats = receipt.expect_commit_success().output(1);
+        assert_eq!(stats.pending_tickets, 0);
+        assert_eq!(stats.tickets_minted, 5);
+    }
+
+    #[test]
+    fn test_bond_manifest_fee_and_badges() {
+        let mut simulator = ShardzSimulator::deploy(seed_chain().head());
+        let account = simulator.account;
+        let fee_token = simulator.ledger.create_fungible_resource(dec!(1000), 18, account);
+        let fungible_badge = simulator.ledger.create_fungible_resource(dec!(1), 0, account);
+        let non_fungible_badge = simulator.ledger.create_non_fungible_resource(account);
+        simulator.manifests.addresses.fee_resource = fee_token;
+        let manifests = simulator.manifests.clone();
+
+        simulator.execute(manifests.badge_call_manifest(account, "set_draw_fee", manifest_args!(fee_token, dec!(10)))).expect_commit_success();
+        for badge in [fungible_badge, non_fungible_badge] {
+            simulator.execute(manifests.badge_call_manifest(account, "add_fee_exempt_badge", manifest_args!(badge))).expect_commit_success();
+        }
+
+        // The payment is only partly taken, the rest is deposited back
+        simulator.execute(manifests.bond_manifest(account, 2, Some(dec!(25)), None, None, None)).expect_commit_success();
+        assert_eq!(simulator.balance(fee_token), dec!(980));
+        simulator.execute(manifests.bond_manifest(account, 2, None, None, None, None)).expect_commit_failure();
+
+        let fungible = BadgeProof::Fungible(fungible_badge, dec!(1));
+        simulator.execute(manifests.bond_manifest(account, 2, None, Some(fungible), None, None)).expect_commit_success();
+        let non_fungible = BadgeProof::NonFungible(NonFungibleGlobalId::new(non_fungible_badge, NonFungibleLocalId::integer(1)));
+        simulator.execute(manifests.bond_manifest(account, 2, None, Some(non_fungible), None, None)).expect_commit_success();
+        assert_eq!(simulator.balance(fee_token), dec!(980));
+        assert_eq!(simulator.balance(manifests.addresses.ticket), dec!(6));
+
+        simulator.execute(manifests.admin(account).withdraw_treasury_manifest(fee_token, dec!(20))).expect_commit_success();
+        assert_eq!(simulator.balance(fee_token), dec!(1000));
+    }
+
+    #[test]
+    fn test_admin_manifests() {
+        let mut simulator = ShardzSimulator::deploy(seed_chain().head());
+        let (manifests, account) = (simulator.manifests.clone(), simulator.account);
+        let admin = manifests.admin(account);
+
+        simulator.execute(admin.set_paused_manifest(true)).expect_commit_success();
+        simulator.execute(manifests.bond_manifest(account, 1, None, None, None, None)).expect_commit_failure();
+        simulator.execute(admin.set_paused_manifest(false)).expect_commit_success();
+        simulator.execute(manifests.bond_manifest(account, 1, None, None, None, None)).expect_commit_success();
+
+        simulator.execute(admin.set_royalty_manifest("bond", RoyaltyAmount::Xrd(dec!(2)))).expect_commit_success();
+        simulator.execute(manifests.bond_manifest(account, 1, None, None, None, None)).expect_commit_success();
+        simulator.execute(admin.claim_royalties_manifest()).expect_commit_success();
+
+        simulator.execute(admin.set_metadata_manifest("name", "Shardz Test".to_string())).expect_commit_success();
+        let name = simulator.ledger.get_metadata(manifests.addresses.component.into(), "name");
+        assert_eq!(name, Some(MetadataValue::String("Shardz Test".to_string())));
+
+        let commitment = Hash([9; 32]);
+        simulator.execute(admin.commit_seed_manifest(commitment)).expect_commit_success();
+        simulator.execute(admin.commit_seed_manifest(commitment)).expect_commit_failure();
+
+        let receipt = simulator.execute_with_faucet_fee(stats_manifest(manifests.addresses.component));
+        let stats: ShardzStats = receipt.expect_commit_success().output(1);
+        assert!(!stats.paused);
+        assert_eq!(stats.tickets_minted, 2);
+    }
+}
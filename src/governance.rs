@@ -0,0 +1,51 @@
+use scrypto::prelude::*;
+
+use crate::shardz::{CustomTier, ShardType};
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub enum ProposalAction {
+    SetRerollCooldown(u32),
+    SetUnbondTimeout(u32),
+    SetDrawFee(ResourceAddress, Decimal),
+    SetTierWeights(Vec<(ShardType, u32)>),
+    AddTier(CustomTier, u32),
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct Proposal {
+    pub action: ProposalAction,
+    pub proposer: NonFungibleLocalId,
+    pub voting_end: Instant,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub executed: bool,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct GovernanceConfig {
+    /// How long a proposal can be voted on after its creation
+    pub voting_period_hours: u32,
+    /// Delay between the end of the vote and the execution of a passed proposal
+    pub timelock_hours: u32,
+    /// Minimum voting power that must take part in a vote for it to be valid
+    pub quorum: u64,
+    /// Minimum voting power of the shards proven to create a proposal
+    pub proposal_threshold: u64,
+}
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            voting_period_hours: 72,
+            timelock_hours: 24,
+            quorum: 100,
+            proposal_threshold: 1,
+        }
+    }
+}
+
+impl Proposal {
+    pub fn has_passed(&self, quorum: u64) -> bool {
+        self.votes_for + self.votes_against >= quorum && self.votes_for > self.votes_against
+    }
+}
@@ -1 +1,2 @@
+pub mod governance;
 pub mod shardz;
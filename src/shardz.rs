@@ -1,6 +1,9 @@
+use scrypto::engine::scrypto_env::ScryptoVmV1Api;
 use scrypto::prelude::*;
 
-#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+use crate::governance::{GovernanceConfig, Proposal, ProposalAction};
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Eq, Hash, Debug, Clone)]
 pub enum ShardType {
     Clear,
     Yellow,
@@ -9,7 +12,16 @@ pub enum ShardType {
     Emerald,
     Scrypto,
     Radix,
-    Xian
+    Xian,
+    Custom(u16),
+}
+
+/// A tier added after instantiation, described by the component rather than by `ShardType`
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct CustomTier {
+    pub name: String,
+    pub key_image_url: String,
+    pub rarity: u8,
 }
 
 #[derive(NonFungibleData, ScryptoSbor, Debug)]
@@ -19,12 +31,128 @@ pub struct ShardNFT {
     pub shard_type: ShardType,
     fungible_address: ResourceAddress,
     mint_time: Instant,
+    pub season_id: Option<u64>,
 }
 
 #[derive(NonFungibleData, ScryptoSbor, PartialEq, Debug)]
 pub struct ShardTicket {
     #[mutable]
-    pub shard_type: Option<ShardType>
+    pub shard_type: Option<ShardType>,
+    pub bond_time: Instant,
+    pub profile_id: Option<NonFungibleLocalId>,
+    /// Expired tickets cannot be swapped anymore and can only be recycled
+    pub expires_at: Instant,
+    /// Resource and part of the draw fee of this ticket kept by the treasury, after the jackpot and referral shares.
+    /// It caps the keeper tip paid back when the ticket is recycled.
+    pub treasury_fee: Option<(ResourceAddress, Decimal)>,
+    /// Seed commitment of the drawer when the ticket was bonded, its seed must be revealed to draw the ticket
+    pub seed_commitment: Hash,
+    /// Hash of the transaction that bonded the ticket, mixed with the revealed seed
+    pub bond_hash: Hash,
+}
+
+/// Lets the holder use a rented shard until the pass expires
+#[derive(NonFungibleData, ScryptoSbor, PartialEq, Debug)]
+pub struct RentalPass {
+    pub shard_id: NonFungibleLocalId,
+    pub shard_type: ShardType,
+    pub expires_at: Instant,
+}
+
+/// Held by the owner of a shard listed for rent, ids are never reused since burned ids cannot be minted again
+#[derive(NonFungibleData, ScryptoSbor, PartialEq, Debug)]
+pub struct RentalReceipt {
+    pub shard_id: NonFungibleLocalId,
+    pub shard_type: ShardType,
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct RentalListing {
+    pub payment_resource: ResourceAddress,
+    pub price_per_hour: Decimal,
+    pub max_hours: u32,
+    pub rented_until: Option<Instant>,
+}
+
+/// Held by a borrower, ids are never reused so that the receipt of a liquidated loan cannot repay a later loan
+#[derive(NonFungibleData, ScryptoSbor, PartialEq, Debug)]
+pub struct LoanReceipt {
+    pub shard_id: NonFungibleLocalId,
+    pub shard_type: ShardType,
+    pub principal: Decimal,
+    pub deadline: Instant,
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct Loan {
+    /// Receipt minted for the loan, the only one that can repay it
+    pub receipt_id: NonFungibleLocalId,
+    pub shard_type: ShardType,
+    pub principal: Decimal,
+    pub start: Instant,
+    pub deadline: Instant,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct LoanConfig {
+    /// Amount of SHARD lent per shard of each tier, as a ratio of the 1 SHARD a shard is worth when destroyed
+    pub tier_ltv: Vec<(ShardType, Decimal)>,
+    /// Simple interest accrued per hour on the principal
+    pub interest_per_hour: Decimal,
+    /// Delay after which a loan that was not repaid can be liquidated
+    pub duration_hours: u32,
+}
+
+impl Default for LoanConfig {
+    fn default() -> Self {
+        Self {
+            tier_ltv: vec![
+                (ShardType::Clear, dec!("0.5")),
+                (ShardType::Yellow, dec!("0.6")),
+                (ShardType::Orange, dec!("0.7")),
+                (ShardType::Blue, dec!("0.8")),
+                (ShardType::Emerald, dec!(1)),
+                (ShardType::Scrypto, dec!("1.5")),
+                (ShardType::Radix, dec!(2)),
+                (ShardType::Xian, dec!(5)),
+            ],
+            interest_per_hour: dec!("0.00001"),
+            duration_hours: 720,
+        }
+    }
+}
+
+impl Loan {
+    /// Principal and interest owed at the given time, interest stops accruing at the deadline
+    pub fn owed(&self, interest_per_hour: Decimal, at: Instant) -> Decimal {
+        let end = at.seconds_since_unix_epoch.min(self.deadline.seconds_since_unix_epoch);
+        let hours = (end - self.start.seconds_since_unix_epoch).max(0) / 3600;
+
+        self.principal + self.principal * interest_per_hour * hours
+    }
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub enum Achievement {
+    FirstShard,
+    FirstXian,
+    HundredTickets,
+    HundredRerolls,
+}
+
+#[derive(NonFungibleData, ScryptoSbor, PartialEq, Debug)]
+pub struct PlayerProfile {
+    /// Number of consecutive draws below the pity tier
+    #[mutable]
+    pub pity_counter: u32,
+    #[mutable]
+    pub tickets_bonded: u64,
+    #[mutable]
+    pub shards_pulled: IndexMap<ShardType, u64>,
+    #[mutable]
+    pub rerolls: u64,
+    #[mutable]
+    pub achievements: Vec<Achievement>,
 }
 
 impl ShardType {
@@ -38,6 +166,7 @@ impl ShardType {
             ShardType::Scrypto => { Url::of("https://ipfs.dexteronradix.com/ipfs/bafybeid2tvh6hy5oqupqjhhn2nbxasube4z22ikgiq4nednkimta6ckdai/") }
             ShardType::Radix => { Url::of("https://ipfs.dexteronradix.com/ipfs/bafybeic4j2jv35mtqz2t4hoyaujh4ksknlsu2dfheybyigiwufurvquu2m/") }
             ShardType::Xian => { Url::of("https://ipfs.dexteronradix.com/ipfs/bafybeie7ytmrxskdsxr4e2axci6k2756jelfldcgannia5575yqj5pqbci/") }
+            ShardType::Custom(_) => { unreachable!("Custom tiers are described by the component") }
         }
     }
 
@@ -51,29 +180,492 @@ impl ShardType {
             ShardType::Scrypto => { "Scrypto Shard".to_string() }
             ShardType::Radix => { "Radix Shard".to_string() }
             ShardType::Xian => { "Xian Shard".to_string() }
+            ShardType::Custom(_) => { unreachable!("Custom tiers are described by the component") }
+        }
+    }
+
+    /// Rank of the tier, from 0 for Clear up to 7 for Xian
+    pub fn rarity(&self) -> u8 {
+        match self {
+            ShardType::Clear => 0,
+            ShardType::Yellow => 1,
+            ShardType::Orange => 2,
+            ShardType::Blue => 3,
+            ShardType::Emerald => 4,
+            ShardType::Scrypto => 5,
+            ShardType::Radix => 6,
+            ShardType::Xian => 7,
+            ShardType::Custom(_) => { unreachable!("Custom tiers are described by the component") }
+        }
+    }
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct JackpotConfig {
+    /// Share of each draw fee sent to the jackpot pool
+    pub fee_share: Decimal,
+    /// Tier that wins the jackpot when swapped from a ticket, it must have a draw weight
+    pub tier: ShardType,
+    /// Share of the pool paid to each winner
+    pub payout: Decimal,
+}
+
+impl Default for JackpotConfig {
+    fn default() -> Self {
+        Self {
+            fee_share: dec!("0.1"),
+            tier: ShardType::Radix,
+            payout: dec!("0.5"),
+        }
+    }
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct PityConfig {
+    /// Number of consecutive draws below the pity tier after which the next draw is guaranteed
+    pub threshold: u32,
+    /// Lowest tier guaranteed by the pity counter
+    pub tier: ShardType,
+}
+
+impl Default for PityConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 50,
+            tier: ShardType::Blue,
+        }
+    }
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub enum PhaseAccess {
+    Public,
+    /// Requires a proof of a non fungible badge of the given resource
+    Badge(ResourceAddress),
+    /// Requires a proof of a non fungible badge registered in the allowlist of the phase
+    Allowlist,
+}
+
+/// Who gets credited with a share of the draw fee when referring a player
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Eq, Hash, Debug, Clone)]
+pub enum Referrer {
+    /// A player profile, rewards are claimed with a proof of the profile
+    Profile(NonFungibleLocalId),
+}
+
+/// A launch phase restricting `bond` from its start until the start of the next phase
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug, Clone)]
+pub struct LaunchPhase {
+    pub name: String,
+    pub start: Instant,
+    pub access: PhaseAccess,
+    /// Maximum number of tickets bonded per badge during the phase
+    pub ticket_cap: Option<u64>,
+    /// Draw fee per ticket during the phase, the default draw fee applies if not set
+    pub price: Option<Decimal>,
+}
+
+/// A limited time window during which extra tiers can be drawn
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct Season {
+    pub name: String,
+    pub start: Instant,
+    pub end: Instant,
+    pub tier_weights: Vec<(ShardType, u32)>,
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct JackpotWin {
+    pub nft_id: NonFungibleLocalId,
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+    pub time: Instant,
+}
+
+#[derive(ScryptoSbor, PartialEq, Debug, Clone)]
+pub struct ShardzStats {
+    pub tickets_minted: u64,
+    pub shards_minted: u64,
+    /// Tickets bonded and not drawn yet, tickets unbonded or recycled before being drawn are not counted
+    pub pending_tickets: u64,
+    pub shards_in_circulation: Decimal,
+    pub tickets_in_circulation: Decimal,
+    pub nfts_in_circulation: Decimal,
+    pub paused: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct TicketsMintedEvent {
+    pub ticket_ids: Vec<NonFungibleLocalId>,
+    /// Set for tickets airdropped already drawn
+    pub shard_type: Option<ShardType>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct TicketDrawnEvent {
+    pub ticket_id: NonFungibleLocalId,
+    pub shard_type: ShardType,
+    /// Seed of the draw, derived from the revealed seed and the bond transaction with `ticket_seed`
+    pub seed: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct TicketsSwappedEvent {
+    /// Ticket id, id of the minted shard and its tier
+    pub swaps: Vec<(NonFungibleLocalId, NonFungibleLocalId, ShardType)>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct TicketsUnbondedEvent {
+    pub ticket_ids: Vec<NonFungibleLocalId>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct TicketsRecycledEvent {
+    pub ticket_ids: Vec<NonFungibleLocalId>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, PartialEq, Debug)]
+pub struct ShardsDestroyedEvent {
+    pub nft_ids: Vec<NonFungibleLocalId>,
+}
+
+/// Mints non fungibles in a single call to the resource manager rather than one call per id.
+/// The `ResourceManager` of Scrypto 1.2 only mints one non fungible per call, so this invokes the native `mint` method,
+/// which takes every entry at once, directly. The data of each entry is passed as a one field tuple like the wrapper does.
+pub fn mint_in_bulk<T: ScryptoEncode>(resource_manager: &ResourceManager, entries: IndexMap<NonFungibleLocalId, T>) -> Bucket {
+    if entries.is_empty() {
+        return Bucket::new(resource_manager.address());
+    }
+
+    let entries: IndexMap<NonFungibleLocalId, (ScryptoValue,)> = entries.into_iter()
+        .map(|(id, data)| {
+            let data = scrypto_encode(&data).expect("Non fungible data could not be encoded");
+            (id, (scrypto_decode(&data).expect("Non fungible data could not be decoded as a value"),))
+        })
+        .collect();
+    let input = scrypto_encode(&NonFungibleResourceManagerMintInput { entries }).expect("Mint input could not be encoded");
+    let output = ScryptoVmV1Api::object_call(
+        resource_manager.address().as_node_id(),
+        NON_FUNGIBLE_RESOURCE_MANAGER_MINT_IDENT,
+        input,
+    );
+
+    scrypto_decode(&output).expect("Mint output is not a bucket")
+}
+
+/// Number of whole units in a floored amount, the amount must fit in a u64
+pub fn whole_units(amount: Decimal) -> u64 {
+    assert!(amount >= Decimal::ZERO, "Amount cannot be negative");
+    u64::try_from(amount.attos() / Decimal::ONE.attos()).expect("Amount is too large")
+}
+
+/// Highest rarity a tier can have, the rarity of Xian, so that no shard votes with more than 128
+pub const MAX_TIER_RARITY: u8 = 7;
+
+/// Voting power of a shard, doubling with each rarity rank
+pub fn voting_power(rarity: u8) -> u64 {
+    1u64 << rarity.min(MAX_TIER_RARITY)
+}
+
+/// The jackpot and referral shares are both taken from the draw fee, the treasury keeps the rest
+fn assert_fee_shares(jackpot_share: Decimal, referral_share: Decimal) {
+    assert!(jackpot_share + referral_share <= Decimal::ONE, "The jackpot and referral shares cannot exceed the draw fee");
+}
+
+fn assert_tier_rarity(tier: &CustomTier) {
+    assert!(tier.rarity <= MAX_TIER_RARITY, "The rarity of a tier cannot exceed {}", MAX_TIER_RARITY);
+}
+
+fn put_in_vault<K: ScryptoEncode + ScryptoDecode + ScryptoDescribe>(vaults: &KeyValueStore<K, Vault>, key: K, bucket: Bucket) {
+    let has_vault = vaults.get(&key).is_some();
+
+    if has_vault {
+        vaults.get_mut(&key).unwrap().put(bucket);
+    } else {
+        vaults.insert(key, Vault::with_bucket(bucket));
+    }
+}
+
+fn hours_after(instant: Instant, hours: u32) -> Instant {
+    let utc = UtcDateTime::try_from(instant).unwrap();
+    Instant::from(utc.add_hours(hours as i64).unwrap())
+}
+
+impl PlayerProfile {
+    pub fn new() -> Self {
+        Self {
+            pity_counter: 0,
+            tickets_bonded: 0,
+            shards_pulled: index_map_new(),
+            rerolls: 0,
+            achievements: Vec::new(),
+        }
+    }
+
+    /// Achievements reached by the profile stats that were not unlocked yet
+    pub fn new_achievements(&self) -> Vec<Achievement> {
+        let total_pulled: u64 = self.shards_pulled.values().sum();
+        let conditions = [
+            (Achievement::FirstShard, total_pulled >= 1),
+            (Achievement::FirstXian, self.shards_pulled.contains_key(&ShardType::Xian)),
+            (Achievement::HundredTickets, self.tickets_bonded >= 100),
+            (Achievement::HundredRerolls, self.rerolls >= 100),
+        ];
+
+        conditions.into_iter()
+            .filter(|(achievement, reached)| *reached && !self.achievements.contains(achievement))
+            .map(|(achievement, _)| achievement)
+            .collect()
+    }
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default draw weights, out of 10000, matching the probabilities published in the README.
+/// Emerald and Xian shards are not drawn unless the owner or governance gives them a weight.
+pub fn default_tier_weights() -> Vec<(ShardType, u32)> {
+    vec![
+        (ShardType::Clear, 3800),
+        (ShardType::Yellow, 2800),
+        (ShardType::Orange, 2000),
+        (ShardType::Blue, 1000),
+        (ShardType::Emerald, 0),
+        (ShardType::Scrypto, 300),
+        (ShardType::Radix, 100),
+        (ShardType::Xian, 0),
+    ]
+}
+
+/// Commitment to a seed of the drawer: seeds are revealed backwards along a hash chain, so that each revealed seed
+/// is the commitment to the next one
+pub fn seed_commitment(seed: &Hash) -> Hash {
+    hash(seed.0)
+}
+
+/// Seed of a ticket's draw, mixing the seed revealed by the drawer with the hash of the transaction that bonded the ticket.
+/// The drawer committed to its seed before the ticket was bonded, so it cannot pick a seed for a known ticket.
+pub fn ticket_seed(revealed: &Hash, bond_hash: &Hash) -> u64 {
+    let digest = hash([revealed.0, bond_hash.0].concat());
+    u64::from_le_bytes(digest.0[..8].try_into().unwrap())
+}
+
+/// Hash chain kept off-ledger by the drawer. Its head is committed first and the seeds before it are revealed one by one.
+pub struct SeedChain {
+    seeds: Vec<Hash>,
+}
+
+impl SeedChain {
+    pub fn new(secret: [u8; 32], length: usize) -> Self {
+        let mut seeds = vec![Hash(secret)];
+        for _ in 1..length.max(2) {
+            let next = seed_commitment(seeds.last().unwrap());
+            seeds.push(next);
+        }
+
+        Self { seeds }
+    }
+
+    /// Commitment to register when instantiating the component or with `commit_seed`
+    pub fn head(&self) -> Hash {
+        *self.seeds.last().unwrap()
+    }
+
+    /// Seed revealing a commitment of the chain, `None` once the chain is used up
+    pub fn reveal(&self, commitment: &Hash) -> Option<Hash> {
+        let position = self.seeds.iter().position(|seed| seed == commitment)?;
+        position.checked_sub(1).map(|previous| self.seeds[previous])
+    }
+}
+
+/// Picks a tier for a ticket from the given weights.
+/// The roll only depends on the ticket id and the seed so that any draw can be verified off-ledger.
+pub fn draw_tier(weights: &[(ShardType, u32)], ticket_id: &NonFungibleLocalId, seed: u64) -> ShardType {
+    let total_weight: u64 = weights.iter().map(|(_, weight)| *weight as u64).sum();
+    assert!(total_weight > 0, "Tier weights cannot all be zero");
+
+    let mut preimage = scrypto_encode(ticket_id).unwrap();
+    preimage.extend_from_slice(&seed.to_le_bytes());
+    let digest = hash(preimage);
+
+    let mut roll = u64::from_le_bytes(digest.0[..8].try_into().unwrap()) % total_weight;
+    for (shard_type, weight) in weights {
+        if roll < *weight as u64 {
+            return shard_type.clone();
         }
+        roll -= *weight as u64;
     }
+
+    unreachable!()
 }
 
 #[blueprint]
-#[types(ShardTicket, ShardNFT)]
+#[events(TicketsMintedEvent, TicketDrawnEvent, TicketsSwappedEvent, TicketsUnbondedEvent, TicketsRecycledEvent, ShardsDestroyedEvent)]
+#[types(ShardTicket, ShardNFT, PlayerProfile, RentalPass, RentalReceipt, RentalListing, LoanReceipt, Loan, CustomTier, Proposal, JackpotWin, Season)]
 mod rrc404 {
 
+    enable_method_auth! {
+        roles {
+            drawer => updatable_by: [OWNER];
+        },
+        methods {
+            bond => PUBLIC;
+            swap_tickets => PUBLIC;
+            drawn_status => PUBLIC;
+            pending_tickets => PUBLIC;
+            advance_pending_queue => PUBLIC;
+            draw_batch => restrict_to: [drawer];
+            seed_commitment => PUBLIC;
+            commit_seed => restrict_to: [drawer];
+            destroy => PUBLIC;
+            unbond => PUBLIC;
+            set_unbond_timeout => restrict_to: [OWNER];
+            set_tier_weights => restrict_to: [OWNER];
+            quote_draw_fee => PUBLIC;
+            set_draw_fee => restrict_to: [OWNER];
+            set_volume_discounts => restrict_to: [OWNER];
+            add_fee_exempt_badge => restrict_to: [OWNER];
+            remove_fee_exempt_badge => restrict_to: [OWNER];
+            withdraw_treasury => restrict_to: [OWNER];
+            set_reroll_cooldown => restrict_to: [OWNER];
+            add_tier => restrict_to: [OWNER];
+            set_governance_config => restrict_to: [OWNER];
+            create_proposal => PUBLIC;
+            vote => PUBLIC;
+            execute_proposal => PUBLIC;
+            get_proposal => PUBLIC;
+            set_jackpot_config => restrict_to: [OWNER];
+            jackpot_amount => PUBLIC;
+            withdraw_jackpot => restrict_to: [OWNER];
+            jackpot_wins => PUBLIC;
+            create_profile => PUBLIC;
+            set_pity_config => restrict_to: [OWNER];
+            schedule_season => restrict_to: [OWNER];
+            get_season => PUBLIC;
+            set_launch_phases => restrict_to: [OWNER];
+            add_to_allowlist => restrict_to: [OWNER];
+            remove_from_allowlist => restrict_to: [OWNER];
+            current_phase => PUBLIC;
+            set_referral_share => restrict_to: [OWNER];
+            referral_rewards => PUBLIC;
+            claim_referral_rewards => PUBLIC;
+            airdrop_tickets => restrict_to: [OWNER];
+            airdrop_shards => restrict_to: [OWNER];
+            unclaimed_airdrop => PUBLIC;
+            claim_airdrop => PUBLIC;
+            list_for_rent => PUBLIC;
+            rent => PUBLIC;
+            reclaim_rental => PUBLIC;
+            get_rental_listing => PUBLIC;
+            set_paused => restrict_to: [OWNER];
+            set_max_tickets_per_bond => restrict_to: [OWNER];
+            set_ticket_lifetime => restrict_to: [OWNER];
+            set_keeper_tip => restrict_to: [OWNER];
+            recycle_expired => PUBLIC;
+            banked_dust => PUBLIC;
+            withdraw_dust => PUBLIC;
+            stats => PUBLIC;
+            set_loan_config => restrict_to: [OWNER];
+            supply_liquidity => PUBLIC;
+            withdraw_liquidity => PUBLIC;
+            lending_pool_value => PUBLIC;
+            borrow => PUBLIC;
+            repay => PUBLIC;
+            loan_owed => PUBLIC;
+            liquidate => PUBLIC;
+            claim_liquidated_shard => PUBLIC;
+        }
+    }
+
     const SHARDZ_BADGE: ResourceAddress = ResourceAddress::new_or_panic([93, 234, 158, 5, 11, 143, 100, 156, 203, 137, 140, 82, 189, 231, 139, 42, 183, 255, 29, 40, 228, 152, 189, 32, 191, 126, 184, 201, 245, 89]);
 
+    /// Maximum number of tickets swapped or shards destroyed in a single call, keeping the transaction within the cost unit limit
+    const MAX_ITEMS_PER_CALL: usize = 200;
+
+    /// Maximum number of queue entries visited by `pending_tickets`, drawn tickets waiting to be removed included
+    const MAX_PENDING_SCAN: u64 = 1000;
+
     const SHARDZ_DESCRIPTION: &str = "Shardz is a revolutionary NFT mini game built on the Radix ledger. 1000 tokens can be shattered and bonded in an attempt to find the rarest shards.";
 
     struct Shardz {
         shardz_fungible: ResourceManager,
         shardz_nft: ResourceManager,
         shardz_ticket: ResourceManager,
+        shardz_profile: ResourceManager,
+        shardz_rental_pass: ResourceManager,
+        shardz_rental_receipt: ResourceManager,
+        shardz_pool_unit: ResourceManager,
+        shardz_loan_receipt: ResourceManager,
         nft_counter: u64,
         ticket_counter: u64,
+        unbond_timeout_hours: u32,
+        pending_queue: KeyValueStore<u64, NonFungibleLocalId>,
+        pending_head: u64,
+        pending_tail: u64,
+        undrawn_tickets: u64,
+        seed_commitment: Hash,
+        /// Every seed commitment of the drawer, with its seed once revealed
+        seed_reveals: KeyValueStore<Hash, Option<Hash>>,
+        tier_weights: Vec<(ShardType, u32)>,
+        draw_fee_resource: ResourceAddress,
+        draw_fee: Decimal,
+        volume_discounts: Vec<(u64, Decimal)>,
+        fee_exempt_badges: IndexSet<ResourceAddress>,
+        treasury: KeyValueStore<ResourceAddress, Vault>,
+        reroll_cooldown_hours: u32,
+        custom_tiers: KeyValueStore<u16, CustomTier>,
+        custom_tier_counter: u16,
+        governance_config: GovernanceConfig,
+        proposals: KeyValueStore<u64, Proposal>,
+        proposal_counter: u64,
+        proposal_votes: KeyValueStore<(u64, NonFungibleLocalId), bool>,
+        /// End of the vote of the last proposal created with each shard
+        proposal_locks: KeyValueStore<NonFungibleLocalId, Instant>,
+        jackpot_config: JackpotConfig,
+        jackpot: KeyValueStore<ResourceAddress, Vault>,
+        jackpot_wins: KeyValueStore<u64, JackpotWin>,
+        jackpot_win_counter: u64,
+        profile_counter: u64,
+        pity_config: PityConfig,
+        seasons: KeyValueStore<u64, Season>,
+        season_counter: u64,
+        season_tiers: KeyValueStore<u16, u64>,
+        scheduled_seasons: Vec<u64>,
+        launch_phases: Vec<LaunchPhase>,
+        allowlists: KeyValueStore<(u32, NonFungibleGlobalId), ()>,
+        phase_mints: KeyValueStore<(u32, NonFungibleGlobalId), u64>,
+        referral_share: Decimal,
+        referral_rewards: KeyValueStore<(Referrer, ResourceAddress), Vault>,
+        unclaimed_airdrops: KeyValueStore<(ComponentAddress, ResourceAddress), Vault>,
+        rental_escrow: Vault,
+        rental_listings: KeyValueStore<NonFungibleLocalId, RentalListing>,
+        rental_earnings: KeyValueStore<NonFungibleLocalId, Vault>,
+        rental_pass_counter: u64,
+        rental_receipt_counter: u64,
+        loan_receipt_counter: u64,
+        paused: bool,
+        loan_config: LoanConfig,
+        lending_pool: Vault,
+        outstanding_principal: Decimal,
+        loans: KeyValueStore<NonFungibleLocalId, Loan>,
+        loan_collateral: Vault,
+        liquidated_shards: Vault,
+        liquidated_values: KeyValueStore<NonFungibleLocalId, Decimal>,
+        liquidated_value: Decimal,
+        dust: KeyValueStore<NonFungibleLocalId, Vault>,
+        max_tickets_per_bond: u64,
+        ticket_lifetime_hours: u32,
+        keeper_tip: Decimal,
     }
 
     impl Shardz {
 
-        pub fn instantiate_shardz(dapp_definition: ComponentAddress) -> (Global<Shardz>, FungibleBucket) {
+        /// `seed_commitment` is the head of the drawer's seed chain, see `SeedChain`
+        pub fn instantiate_shardz(dapp_definition: ComponentAddress, seed_commitment: Hash) -> (Global<Shardz>, FungibleBucket) {
 
             let (address_reservation, component_address) =
                 Runtime::allocate_component_address(<Shardz>::blueprint_id());
@@ -140,31 +732,262 @@ mod rrc404 {
                     burner_updater => rule!(deny_all);
                 })
                 .non_fungible_data_update_roles(non_fungible_data_update_roles! {
-                    non_fungible_data_updater => rule!(require(SHARDZ_BADGE));
+                    non_fungible_data_updater => rule!(require(global_caller(component_address)));
+                    non_fungible_data_updater_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            let shardz_profile = ResourceBuilder::new_integer_non_fungible::<PlayerProfile>(OwnerRole::Fixed(
+                rule!(require(global_caller(component_address)))))
+                .metadata(metadata!(
+                    init {
+                        "name" => "Shardz Player Profile", updatable;
+                        "description" => "Keeps track of a Shardz player across draws", locked;
+                        "icon_url" => Url::of("https://i.ibb.co/23S8X1B/shard-icon.jpg"), updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!{
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                })
+                .withdraw_roles(withdraw_roles! {
+                    withdrawer => rule!(deny_all);
+                    withdrawer_updater => rule!(deny_all);
+                })
+                .non_fungible_data_update_roles(non_fungible_data_update_roles! {
+                    non_fungible_data_updater => rule!(require(global_caller(component_address)));
                     non_fungible_data_updater_updater => rule!(deny_all);
                 })
                 .create_with_no_initial_supply();
 
+            let shardz_rental_pass = ResourceBuilder::new_integer_non_fungible::<RentalPass>(OwnerRole::Fixed(
+                rule!(require(global_caller(component_address)))))
+                .metadata(metadata!(
+                    init {
+                        "name" => "Shard Rental Pass", updatable;
+                        "description" => "Lets the holder use a rented shard until the pass expires", locked;
+                        "icon_url" => Url::of("https://i.ibb.co/23S8X1B/shard-icon.jpg"), updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!{
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            let shardz_rental_receipt = ResourceBuilder::new_integer_non_fungible::<RentalReceipt>(OwnerRole::Fixed(
+                rule!(require(global_caller(component_address)))))
+                .metadata(metadata!(
+                    init {
+                        "name" => "Shard Rental Receipt", updatable;
+                        "description" => "Can be traded back for a shard listed for rent and its earnings", locked;
+                        "icon_url" => Url::of("https://i.ibb.co/23S8X1B/shard-icon.jpg"), updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!{
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            let shardz_pool_unit = ResourceBuilder::new_fungible(OwnerRole::Fixed(
+                rule!(require(global_caller(component_address)))))
+                .divisibility(DIVISIBILITY_MAXIMUM)
+                .metadata(metadata! {
+                    init {
+                        "name" => "Shard Lending Pool Unit", locked;
+                        "symbol" => "SHARDLP", locked;
+                        "description" => "Share of the SHARD lending pool", locked;
+                        "icon_url" => Url::of("https://i.ibb.co/23S8X1B/shard-icon.jpg"), updatable;
+                    }
+                })
+                .mint_roles(mint_roles!{
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            let shardz_loan_receipt = ResourceBuilder::new_integer_non_fungible::<LoanReceipt>(OwnerRole::Fixed(
+                rule!(require(global_caller(component_address)))))
+                .metadata(metadata!(
+                    init {
+                        "name" => "Shard Loan Receipt", updatable;
+                        "description" => "Can be traded back for the shard locked as collateral once the loan is repaid", locked;
+                        "icon_url" => Url::of("https://i.ibb.co/23S8X1B/shard-icon.jpg"), updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!{
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            let rental_escrow = Vault::new(shardz_nft.address());
+            let lending_pool = Vault::new(shardz_fungible.resource_address());
+            let loan_collateral = Vault::new(shardz_nft.address());
+            let liquidated_shards = Vault::new(shardz_nft.address());
+            let seed_reveals = KeyValueStore::new();
+            seed_reveals.insert(seed_commitment, None);
+
             let component = Self {
                 shardz_fungible: shardz_fungible.resource_manager(),
                 shardz_nft,
                 shardz_ticket,
+                shardz_profile,
+                shardz_rental_pass,
+                shardz_rental_receipt,
+                shardz_pool_unit,
+                shardz_loan_receipt,
                 nft_counter: 1,
-                ticket_counter: 1
+                ticket_counter: 1,
+                unbond_timeout_hours: 24,
+                pending_queue: KeyValueStore::new(),
+                pending_head: 0,
+                pending_tail: 0,
+                undrawn_tickets: 0,
+                seed_commitment,
+                seed_reveals,
+                tier_weights: default_tier_weights(),
+                draw_fee_resource: XRD,
+                draw_fee: Decimal::ZERO,
+                volume_discounts: Vec::new(),
+                fee_exempt_badges: index_set_new(),
+                treasury: KeyValueStore::new(),
+                reroll_cooldown_hours: 4,
+                custom_tiers: KeyValueStore::new(),
+                custom_tier_counter: 0,
+                governance_config: GovernanceConfig::default(),
+                proposals: KeyValueStore::new(),
+                proposal_counter: 0,
+                proposal_votes: KeyValueStore::new(),
+                proposal_locks: KeyValueStore::new(),
+                jackpot_config: JackpotConfig::default(),
+                jackpot: KeyValueStore::new(),
+                jackpot_wins: KeyValueStore::new(),
+                jackpot_win_counter: 0,
+                profile_counter: 1,
+                pity_config: PityConfig::default(),
+                seasons: KeyValueStore::new(),
+                season_counter: 0,
+                season_tiers: KeyValueStore::new(),
+                scheduled_seasons: Vec::new(),
+                launch_phases: Vec::new(),
+                allowlists: KeyValueStore::new(),
+                phase_mints: KeyValueStore::new(),
+                referral_share: dec!("0.05"),
+                referral_rewards: KeyValueStore::new(),
+                unclaimed_airdrops: KeyValueStore::new(),
+                rental_escrow,
+                rental_listings: KeyValueStore::new(),
+                rental_earnings: KeyValueStore::new(),
+                rental_pass_counter: 1,
+                rental_receipt_counter: 1,
+                loan_receipt_counter: 1,
+                paused: false,
+                loan_config: LoanConfig::default(),
+                lending_pool,
+                outstanding_principal: Decimal::ZERO,
+                loans: KeyValueStore::new(),
+                loan_collateral,
+                liquidated_shards,
+                liquidated_values: KeyValueStore::new(),
+                liquidated_value: Decimal::ZERO,
+                dust: KeyValueStore::new(),
+                max_tickets_per_bond: 500,
+                ticket_lifetime_hours: 720,
+                keeper_tip: dec!("0.1"),
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::Fixed(rule!(require(SHARDZ_BADGE))))
+            .roles(roles!(
+                drawer => rule!(require(SHARDZ_BADGE));
+            ))
             .with_address(address_reservation)
             .enable_component_royalties(component_royalties! {
                 init {
                     bond => Xrd(1.into()), updatable;
                     destroy => Xrd(1.into()), updatable;
                     swap_tickets => Free, updatable;
+                    drawn_status => Free, locked;
+                    pending_tickets => Free, locked;
+                    advance_pending_queue => Free, locked;
+                    draw_batch => Free, locked;
+                    seed_commitment => Free, locked;
+                    commit_seed => Free, locked;
+                    unbond => Free, updatable;
+                    set_unbond_timeout => Free, locked;
+                    set_tier_weights => Free, locked;
+                    quote_draw_fee => Free, locked;
+                    set_draw_fee => Free, locked;
+                    set_volume_discounts => Free, locked;
+                    add_fee_exempt_badge => Free, locked;
+                    remove_fee_exempt_badge => Free, locked;
+                    withdraw_treasury => Free, locked;
+                    set_reroll_cooldown => Free, locked;
+                    add_tier => Free, locked;
+                    set_governance_config => Free, locked;
+                    create_proposal => Free, updatable;
+                    vote => Free, updatable;
+                    execute_proposal => Free, locked;
+                    get_proposal => Free, locked;
+                    set_jackpot_config => Free, locked;
+                    jackpot_amount => Free, locked;
+                    withdraw_jackpot => Free, locked;
+                    jackpot_wins => Free, locked;
+                    create_profile => Free, updatable;
+                    set_pity_config => Free, locked;
+                    schedule_season => Free, locked;
+                    get_season => Free, locked;
+                    set_launch_phases => Free, locked;
+                    add_to_allowlist => Free, locked;
+                    remove_from_allowlist => Free, locked;
+                    current_phase => Free, locked;
+                    set_referral_share => Free, locked;
+                    referral_rewards => Free, locked;
+                    claim_referral_rewards => Free, updatable;
+                    airdrop_tickets => Free, locked;
+                    airdrop_shards => Free, locked;
+                    unclaimed_airdrop => Free, locked;
+                    claim_airdrop => Free, updatable;
+                    list_for_rent => Free, updatable;
+                    rent => Free, updatable;
+                    reclaim_rental => Free, updatable;
+                    get_rental_listing => Free, locked;
+                    set_paused => Free, locked;
+                    set_max_tickets_per_bond => Free, locked;
+                    set_ticket_lifetime => Free, locked;
+                    set_keeper_tip => Free, locked;
+                    recycle_expired => Free, updatable;
+                    banked_dust => Free, locked;
+                    withdraw_dust => Free, updatable;
+                    stats => Free, locked;
+                    set_loan_config => Free, locked;
+                    supply_liquidity => Free, updatable;
+                    withdraw_liquidity => Free, updatable;
+                    lending_pool_value => Free, locked;
+                    borrow => Free, updatable;
+                    repay => Free, updatable;
+                    loan_owed => Free, locked;
+                    liquidate => Free, locked;
+                    claim_liquidated_shard => Free, updatable;
                 }
             }).metadata(metadata!(roles {
-            metadata_setter => rule!(deny_all);
+            // The owner can update the unlocked metadata, e.g. through the set-metadata command of shardz-cli
+            metadata_setter => OWNER;
             metadata_setter_updater => rule!(deny_all);
-            metadata_locker => rule!(deny_all);
+            metadata_locker => OWNER;
             metadata_locker_updater => rule!(deny_all);
             },
             init {
@@ -177,85 +1000,1183 @@ mod rrc404 {
             (component, shardz_fungible)
         }
 
-        pub fn bond(&mut self, mut deposit: Bucket) -> (Bucket, Bucket) {
+        /// The payment of the draw fee is only required when a fee is due, any excess is returned
+        pub fn bond(&mut self, mut deposit: Bucket, mut payment: Option<Bucket>, badge: Option<Proof>, profile: Option<Proof>, referrer: Option<Referrer>) -> (Bucket, Bucket, Option<Bucket>) {
+            assert!(!self.paused, "Shardz is paused");
             assert_eq!(deposit.resource_address(), self.shardz_fungible.address(), "Incorrect resource address");
+
+            // Tickets bonded with a profile count towards its pity counter when drawn
+            let profile_id = self.checked_profile_id(profile);
+
+            // Referred players bond with their own soulbound profile, which cannot be the referrer
+            if let Some(Referrer::Profile(referrer_id)) = &referrer {
+                let profile_id = profile_id.as_ref().expect("A player profile is required to bond with a referrer");
+                assert_ne!(referrer_id, profile_id, "Players cannot refer themselves");
+                assert!(self.shardz_profile.non_fungible_exists(referrer_id), "Unknown referrer");
+            }
         
+            // With a profile, the fractional part of the deposit is banked until it adds up to whole tickets
+            if let Some(profile_id) = &profile_id {
+                let fraction = deposit.amount() - deposit.amount().checked_floor().unwrap();
+                put_in_vault(&self.dust, profile_id.clone(), deposit.take(fraction));
+
+                let mut dust = self.dust.get_mut(profile_id).unwrap();
+                let whole_units = dust.amount().checked_floor().unwrap();
+                deposit.put(dust.take(whole_units));
+            }
+
             let floor_amount = deposit.amount().checked_floor().unwrap();
-            let deposit_amount = floor_amount.to_string().parse::<u64>().unwrap();
-            let mut ticket_bucket: Bucket = Bucket::new(self.shardz_ticket.address());
-        
-            for _ in 0..deposit_amount {
-                let nft_id = NonFungibleLocalId::from(self.ticket_counter);
-                
-                let data = ShardTicket {
-                    shard_type: None,
-                };
-        
-                ticket_bucket.put(self.shardz_ticket.mint_non_fungible(&nft_id, data));
-                self.ticket_counter += 1;
+            assert!(floor_amount <= Decimal::from(self.max_tickets_per_bond),
+                "At most {} tickets can be bonded per call", self.max_tickets_per_bond
+            );
+            let deposit_amount = whole_units(floor_amount);
+
+            // The badge is checked against the launch phase and the fee exempt badges
+            let badge = badge.map(|badge| badge.skip_checking());
+            // With launch phases, bonding is closed until the first one starts
+            let phase_index = self.current_phase_index();
+            assert!(phase_index.is_some() || self.launch_phases.is_empty(), "Bonding has not started yet");
+            if let Some(phase_index) = phase_index {
+                self.check_phase_access(phase_index, badge.as_ref(), deposit_amount);
+            }
+
+            // Badges listed as fee exempt skip the draw fee, e.g. for promotions
+            let fee_exempt = match badge {
+                Some(badge) => {
+                    let exempt = self.fee_exempt_badges.contains(&badge.resource_address()) && badge.amount() > Decimal::ZERO;
+                    badge.drop();
+                    exempt
+                }
+                None => false,
+            };
+
+            let mut treasury_fee = None;
+            if !fee_exempt {
+                let fee = self.quote_draw_fee(deposit_amount);
+                if fee > Decimal::ZERO {
+                    let payment = payment.as_mut().expect("A payment of the draw fee is required");
+                    assert_eq!(payment.resource_address(), self.draw_fee_resource, "Incorrect fee resource address");
+                    assert!(payment.amount() >= fee, "The draw fee for {} tickets is {}", deposit_amount, fee);
+                    let mut fee_bucket = payment.take(fee);
+                    put_in_vault(&self.jackpot, self.draw_fee_resource, fee_bucket.take_advanced(fee * self.jackpot_config.fee_share, WithdrawStrategy::Rounded(RoundingMode::ToZero)));
+                    if let Some(referrer) = referrer {
+                        put_in_vault(&self.referral_rewards, (referrer, self.draw_fee_resource), fee_bucket.take_advanced(fee * self.referral_share, WithdrawStrategy::Rounded(RoundingMode::ToZero)));
+                    }
+                    treasury_fee = Some((self.draw_fee_resource, fee_bucket.amount() / Decimal::from(deposit_amount)));
+                    put_in_vault(&self.treasury, self.draw_fee_resource, fee_bucket);
+                }
             }
         
+            let ticket_bucket = self.mint_tickets(deposit_amount, None, profile_id.clone(), treasury_fee);
+        
             deposit.take(floor_amount).burn();
 
+            if let Some(profile_id) = profile_id {
+                self.record_profile_activity(&profile_id, deposit_amount, &[], 0);
+            }
+
         
-            (ticket_bucket, deposit)
+            (ticket_bucket, deposit, payment)
         }
 
-        pub fn swap_tickets(&mut self, ticket_bucket: Bucket) -> Bucket {
+        pub fn swap_tickets(&mut self, ticket_bucket: Bucket, profile: Option<Proof>) -> (Bucket, Bucket, Bucket) {
+            assert!(!self.paused, "Shardz is paused");
             assert_eq!(ticket_bucket.resource_address(), self.shardz_ticket.address(), "Incorrect resource address");
-            let mut nft_bucket: Bucket = Bucket::new(self.shardz_nft.address());
+            let profile_id = self.checked_profile_id(profile);
+            let mut pulls: Vec<ShardType> = Vec::new();
+            let mut ticket_bucket = ticket_bucket.as_non_fungible();
+            assert!(ticket_bucket.amount() <= Decimal::from(MAX_ITEMS_PER_CALL as u64),
+                "At most {} tickets can be swapped per call", MAX_ITEMS_PER_CALL
+            );
+            let mint_time = Clock::current_time_rounded_to_minutes();
+            let mut tier_details: IndexMap<ShardType, (String, Url)> = index_map_new();
+            let mut entries: IndexMap<NonFungibleLocalId, ShardNFT> = index_map_new();
+            let mut returned_ids: IndexSet<NonFungibleLocalId> = index_set_new();
+            let mut jackpot_bucket: Bucket = Bucket::new(self.draw_fee_resource);
+            let mut swaps: Vec<(NonFungibleLocalId, NonFungibleLocalId, ShardType)> = Vec::new();
 
-            for nft_ticket in ticket_bucket.as_non_fungible().non_fungibles(){
+            for nft_ticket in ticket_bucket.non_fungibles(){
                 let ticket: ShardTicket = nft_ticket.data();
-                if let Some(shard_type) = ticket.shard_type {
+                assert!(!Clock::current_time_is_at_or_after(ticket.expires_at, TimePrecision::Minute),
+                    "Ticket {} has expired and can only be recycled", nft_ticket.local_id()
+                );
+                let season_id = ticket.shard_type.as_ref().and_then(|shard_type| self.season_of(shard_type));
+                if season_id.is_some_and(|season_id| self.season_is_over(season_id)) {
+                    // Tickets drawn as the tier of an ended season are handed back, they can be recycled once expired
+                    returned_ids.insert(nft_ticket.local_id().clone());
+                }
+                else if let Some(shard_type) = ticket.shard_type {
                     let nft_id = NonFungibleLocalId::from(self.nft_counter);
+                    let (name, key_image_url) = tier_details.entry(shard_type.clone())
+                        .or_insert_with(|| self.tier_details(&shard_type))
+                        .clone();
+
+                    if shard_type == self.jackpot_config.tier {
+                        jackpot_bucket.put(self.pay_jackpot(&nft_id));
+                    }
+                    pulls.push(shard_type.clone());
+                    swaps.push((nft_ticket.local_id().clone(), nft_id.clone(), shard_type.clone()));
 
                     let data = ShardNFT {
-                        name: shard_type.name(),
-                        key_image_url: shard_type.url(),
+                        name,
+                        key_image_url,
                         shard_type,
                         fungible_address: self.shardz_fungible.address(),
-                        mint_time: Clock::current_time_rounded_to_minutes(),
+                        mint_time,
+                        season_id,
                     };
 
-                    nft_bucket.put(self.shardz_nft.mint_non_fungible(&nft_id, data));
+                    entries.insert(nft_id, data);
                     self.nft_counter+=1;
                 }
                 else{
-                    panic!("Some ticket were not drawn")
+                    // Undrawn tickets are handed back so that they can be swapped once drawn
+                    returned_ids.insert(nft_ticket.local_id().clone());
                 }
             }
+            let nft_bucket = mint_in_bulk(&self.shardz_nft, entries);
+            let returned_bucket: Bucket = ticket_bucket.take_non_fungibles(&returned_ids).into();
             ticket_bucket.burn();
+            Runtime::emit_event(TicketsSwappedEvent { swaps });
+
+            if let Some(profile_id) = profile_id {
+                self.record_profile_activity(&profile_id, 0, &pulls, 0);
+            }
 
-            nft_bucket
+            (nft_bucket, returned_bucket, jackpot_bucket)
         }
 
+        pub fn drawn_status(&self, ticket_ids: Vec<NonFungibleLocalId>) -> IndexMap<NonFungibleLocalId, bool> {
+            let mut status: IndexMap<NonFungibleLocalId, bool> = index_map_new();
+
+            for ticket_id in ticket_ids {
+                let ticket = self.shardz_ticket.get_non_fungible_data::<ShardTicket>(&ticket_id);
+                status.insert(ticket_id, ticket.shard_type.is_some());
+            }
+
+            status
+        }
+
+        /// Draws tickets with the seed revealed for the commitment they were bonded with.
+        /// `reveal` reveals the seed of the current commitment, which becomes the commitment of the next tickets.
+        pub fn draw_batch(&mut self, ticket_ids: Vec<NonFungibleLocalId>, reveal: Option<Hash>) {
+            if let Some(seed) = reveal {
+                let commitment = seed_commitment(&seed);
+                assert_eq!(commitment, self.seed_commitment, "The revealed seed does not match the seed commitment");
+                self.seed_reveals.insert(commitment, Some(seed));
+                self.seed_reveals.insert(seed, None);
+                self.seed_commitment = seed;
+            }
+
+            let weights = self.current_weights();
+
+            for ticket_id in ticket_ids.iter() {
+                let ticket = self.shardz_ticket.get_non_fungible_data::<ShardTicket>(ticket_id);
+                assert!(ticket.shard_type.is_none(), "Ticket {} was already drawn", ticket_id);
+
+                let revealed = self.revealed_seed(&ticket.seed_commitment)
+                    .unwrap_or_else(|| panic!("The seed of ticket {} has not been revealed", ticket_id));
+                let seed = ticket_seed(&revealed, &ticket.bond_hash);
+
+                let shard_type = match ticket.profile_id {
+                    Some(profile_id) => self.draw_with_pity(&weights, &profile_id, ticket_id, seed),
+                    None => draw_tier(&weights, ticket_id, seed),
+                };
+                self.shardz_ticket.update_non_fungible_data(ticket_id, "shard_type", Some(shard_type.clone()));
+                Runtime::emit_event(TicketDrawnEvent { ticket_id: ticket_id.clone(), shard_type, seed });
+            }
+
+            self.undrawn_tickets -= ticket_ids.len() as u64;
+            self.consume_pending(ticket_ids.len() as u64);
+        }
+
+        /// Commitment that tickets bonded now are drawn with
+        pub fn seed_commitment(&self) -> Hash {
+            self.seed_commitment
+        }
+
+        /// Starts a new seed chain once the current one is used up, tickets bonded with older commitments are still
+        /// drawn with their own seed
+        pub fn commit_seed(&mut self, commitment: Hash) {
+            assert!(self.seed_reveals.get(&commitment).is_none(), "This seed commitment was already used");
+            self.seed_reveals.insert(commitment, None);
+            self.seed_commitment = commitment;
+        }
+
+        fn revealed_seed(&self, commitment: &Hash) -> Option<Hash> {
+            self.seed_reveals.get(commitment).and_then(|seed| *seed)
+        }
+
+
+        pub fn destroy(&mut self, nft_bucket: Bucket, profile: Option<Proof>) -> Bucket{
+            assert!(!self.paused, "Shardz is paused");
 
-        pub fn destroy(&mut self, nft_bucket: Bucket) -> Bucket{
-            
             // Assert resource address matches the resource address of the vault
             assert_eq!(nft_bucket.resource_address(), self.shardz_nft.address(), "Incorrect resource address");
+            let profile_id = self.checked_profile_id(profile);
+            assert!(nft_bucket.amount() <= Decimal::from(MAX_ITEMS_PER_CALL as u64),
+                "At most {} shards can be destroyed per call", MAX_ITEMS_PER_CALL
+            );
 
-            for nft_id in nft_bucket.as_non_fungible().non_fungible_local_ids() {
-                
-                let data = self.shardz_nft.get_non_fungible_data::<ShardNFT>(&nft_id);
+            // The data of each shard is read once, the cooldown is only checked against the last minted one
+            let shards = nft_bucket.as_non_fungible().non_fungibles::<ShardNFT>();
+            let latest_mint = shards.iter()
+                .map(|shard| shard.data().mint_time)
+                .max_by_key(|mint_time| mint_time.seconds_since_unix_epoch);
 
-                // Check that each nft is past a 4-hour cooldown period for re-rolling
-                let last_roll = data.mint_time;
-                let last_roll_utc = UtcDateTime::try_from(last_roll).unwrap();
-                let next_roll_utc= last_roll_utc.add_hours(4).unwrap();
-                let next_roll = Instant::from(next_roll_utc);
+            if let Some(latest_mint) = latest_mint {
+                // Check that every shard is past the cooldown period for re-rolling
+                let next_roll = hours_after(latest_mint, self.reroll_cooldown_hours);
 
                 assert!(Clock::current_time_is_at_or_after(next_roll, TimePrecision::Minute),
-                    "There is a 4 hour delay between minting and rerolling"
+                    "There is a {} hour delay between minting and rerolling", self.reroll_cooldown_hours
                 );
+            }
 
+            let nft_ids: Vec<NonFungibleLocalId> = shards.iter().map(|shard| shard.local_id().clone()).collect();
+            if let Some(profile_id) = profile_id {
+                self.record_profile_activity(&profile_id, 0, &[], nft_ids.len() as u64);
             }
 
             let fungible_bucket = self.shardz_fungible.mint(nft_bucket.amount());
             nft_bucket.burn();
+            Runtime::emit_event(ShardsDestroyedEvent { nft_ids });
 
             fungible_bucket
         }
+
+        pub fn unbond(&mut self, ticket_bucket: Bucket) -> Bucket {
+            assert_eq!(ticket_bucket.resource_address(), self.shardz_ticket.address(), "Incorrect resource address");
+
+            for nft_ticket in ticket_bucket.as_non_fungible().non_fungibles::<ShardTicket>() {
+                let ticket = nft_ticket.data();
+
+                assert!(ticket.shard_type.is_none(), "Drawn tickets cannot be unbonded");
+                // Once its seed is revealed the tier of the ticket is known, it can only be drawn
+                assert!(self.revealed_seed(&ticket.seed_commitment).is_none(),
+                    "The seed of ticket {} was revealed, it can only be drawn", nft_ticket.local_id()
+                );
+
+                // Check that the backend had enough time to draw the ticket
+                let unbond_time = hours_after(ticket.bond_time, self.unbond_timeout_hours);
+
+                assert!(Clock::current_time_is_at_or_after(unbond_time, TimePrecision::Minute),
+                    "Tickets can only be unbonded {} hours after bonding", self.unbond_timeout_hours
+                );
+            }
+
+            let ticket_ids: Vec<NonFungibleLocalId> = ticket_bucket.as_non_fungible().non_fungible_local_ids().into_iter().collect();
+            self.undrawn_tickets -= ticket_ids.len() as u64;
+            let fungible_bucket = self.shardz_fungible.mint(ticket_bucket.amount());
+            ticket_bucket.burn();
+            Runtime::emit_event(TicketsUnbondedEvent { ticket_ids });
+
+            fungible_bucket
+        }
+
+        pub fn set_unbond_timeout(&mut self, hours: u32) {
+            self.unbond_timeout_hours = hours;
+        }
+
+        /// Undrawn tickets from the head of the queue, at most `MAX_PENDING_SCAN` entries are visited so that drawn
+        /// tickets not removed yet by `advance_pending_queue` cannot make the call run out of cost units
+        pub fn pending_tickets(&self, limit: u32) -> Vec<NonFungibleLocalId> {
+            let mut pending: Vec<NonFungibleLocalId> = Vec::new();
+            let mut index = self.pending_head;
+            let scan_end = self.pending_tail.min(self.pending_head + MAX_PENDING_SCAN);
+
+            while index < scan_end && pending.len() < limit as usize {
+                let ticket_id = self.pending_queue.get(&index).unwrap().clone();
+                if self.is_pending(&ticket_id) {
+                    pending.push(ticket_id);
+                }
+                index += 1;
+            }
+
+            pending
+        }
+
+        pub fn advance_pending_queue(&mut self, limit: u32) -> u64 {
+            // Only tickets that were drawn or burned are removed, so anyone can call this safely
+            self.consume_pending(limit as u64)
+        }
+
+        pub fn set_tier_weights(&mut self, tier_weights: Vec<(ShardType, u32)>) {
+            assert!(tier_weights.iter().any(|(_, weight)| *weight > 0), "Tier weights cannot all be zero");
+            for (shard_type, _) in tier_weights.iter() {
+                if let ShardType::Custom(tier_id) = shard_type {
+                    assert!(self.custom_tiers.get(tier_id).is_some(), "Unknown tier {}", tier_id);
+                    assert!(self.season_tiers.get(tier_id).is_none(), "Season tiers can only be drawn during their season");
+                }
+            }
+            self.tier_weights = tier_weights;
+        }
+
+        fn consume_pending(&mut self, limit: u64) -> u64 {
+            let mut removed: u64 = 0;
+
+            while self.pending_head < self.pending_tail && removed < limit {
+                let ticket_id = self.pending_queue.get(&self.pending_head).unwrap().clone();
+                if self.is_pending(&ticket_id) {
+                    break;
+                }
+                self.pending_queue.remove(&self.pending_head);
+                self.pending_head += 1;
+                removed += 1;
+            }
+
+            removed
+        }
+
+        fn is_pending(&self, ticket_id: &NonFungibleLocalId) -> bool {
+            self.shardz_ticket.non_fungible_exists(ticket_id)
+                && self.shardz_ticket.get_non_fungible_data::<ShardTicket>(ticket_id).shard_type.is_none()
+        }
+
+        pub fn quote_draw_fee(&self, ticket_count: u64) -> Decimal {
+            // The best discount whose volume threshold is reached applies
+            let discount = self.volume_discounts.iter()
+                .filter(|(min_tickets, _)| ticket_count >= *min_tickets)
+                .map(|(_, discount)| *discount)
+                .max()
+                .unwrap_or(Decimal::ZERO);
+
+            let draw_fee = self.current_phase_index()
+                .and_then(|phase_index| self.launch_phases[phase_index as usize].price)
+                .unwrap_or(self.draw_fee);
+
+            draw_fee * ticket_count * (Decimal::ONE - discount)
+        }
+
+        pub fn set_draw_fee(&mut self, draw_fee_resource: ResourceAddress, draw_fee: Decimal) {
+            assert!(draw_fee >= Decimal::ZERO, "The draw fee cannot be negative");
+            self.draw_fee_resource = draw_fee_resource;
+            self.draw_fee = draw_fee;
+        }
+
+        pub fn set_volume_discounts(&mut self, volume_discounts: Vec<(u64, Decimal)>) {
+            for (_, discount) in volume_discounts.iter() {
+                assert!(*discount >= Decimal::ZERO && *discount <= Decimal::ONE, "Discounts must be between 0 and 1");
+            }
+            self.volume_discounts = volume_discounts;
+        }
+
+        pub fn add_fee_exempt_badge(&mut self, badge_address: ResourceAddress) {
+            self.fee_exempt_badges.insert(badge_address);
+        }
+
+        pub fn remove_fee_exempt_badge(&mut self, badge_address: ResourceAddress) {
+            self.fee_exempt_badges.swap_remove(&badge_address);
+        }
+
+        pub fn withdraw_treasury(&mut self, resource_address: ResourceAddress, amount: Decimal) -> Bucket {
+            let mut vault = self.treasury.get_mut(&resource_address).expect("The treasury does not hold this resource");
+            vault.take(amount)
+        }
+
+        pub fn set_reroll_cooldown(&mut self, hours: u32) {
+            self.reroll_cooldown_hours = hours;
+        }
+
+        pub fn add_tier(&mut self, tier: CustomTier, weight: u32) -> ShardType {
+            let shard_type = self.register_tier(tier);
+            self.tier_weights.push((shard_type.clone(), weight));
+
+            shard_type
+        }
+
+        pub fn set_governance_config(&mut self, governance_config: GovernanceConfig) {
+            self.governance_config = governance_config;
+        }
+
+        /// The proven shards need the voting power of the proposal threshold and back the proposal until its vote
+        /// ends, so that the number of open proposals is bounded by the shards held
+        pub fn create_proposal(&mut self, shard_proof: Proof, action: ProposalAction) -> u64 {
+            let shards = self.proven_shards(shard_proof);
+            assert!(!shards.is_empty(), "A proof of at least one shard is required to create a proposal");
+            if let ProposalAction::AddTier(tier, _) = &action {
+                assert_tier_rarity(tier);
+            }
+
+            let power: u64 = shards.iter().map(|(_, shard_type)| voting_power(self.tier_rarity(shard_type))).sum();
+            assert!(power >= self.governance_config.proposal_threshold,
+                "The proven shards need a voting power of at least {} to create a proposal", self.governance_config.proposal_threshold
+            );
+
+            let voting_end = hours_after(Clock::current_time_rounded_to_minutes(), self.governance_config.voting_period_hours);
+            for (shard_id, _) in shards.iter() {
+                if let Some(locked_until) = self.proposal_locks.get(shard_id).map(|end| *end) {
+                    assert!(Clock::current_time_is_at_or_after(locked_until, TimePrecision::Minute),
+                        "Shard {} already backs a proposal that is being voted on", shard_id
+                    );
+                }
+                self.proposal_locks.insert(shard_id.clone(), voting_end);
+            }
+
+            let proposal_id = self.proposal_counter;
+            self.proposals.insert(proposal_id, Proposal {
+                action,
+                proposer: shards[0].0.clone(),
+                voting_end,
+                votes_for: 0,
+                votes_against: 0,
+                executed: false,
+            });
+            self.proposal_counter += 1;
+
+            proposal_id
+        }
+
+        pub fn vote(&mut self, proposal_id: u64, shard_proof: Proof, support: bool) {
+            let shards = self.proven_shards(shard_proof);
+            let voting_end = self.proposals.get(&proposal_id).expect("Proposal does not exist").voting_end;
+
+            assert!(!Clock::current_time_is_at_or_after(voting_end, TimePrecision::Minute), "The vote is closed");
+
+            // Each shard can only vote once per proposal, its voting power depends on its rarity
+            let mut power: u64 = 0;
+            for (shard_id, shard_type) in shards {
+                assert!(self.proposal_votes.get(&(proposal_id, shard_id.clone())).is_none(), "Shard {} already voted", shard_id);
+                self.proposal_votes.insert((proposal_id, shard_id), support);
+
+                power += voting_power(self.tier_rarity(&shard_type));
+            }
+
+            let mut proposal = self.proposals.get_mut(&proposal_id).unwrap();
+            if support {
+                proposal.votes_for += power;
+            } else {
+                proposal.votes_against += power;
+            }
+        }
+
+        pub fn execute_proposal(&mut self, proposal_id: u64) {
+            let proposal = self.proposals.get(&proposal_id).expect("Proposal does not exist").clone();
+
+            assert!(!proposal.executed, "Proposal was already executed");
+            assert!(Clock::current_time_is_at_or_after(
+                hours_after(proposal.voting_end, self.governance_config.timelock_hours), TimePrecision::Minute),
+                "Proposal is still timelocked"
+            );
+            assert!(proposal.has_passed(self.governance_config.quorum), "Proposal did not pass");
+
+            self.proposals.get_mut(&proposal_id).unwrap().executed = true;
+
+            match proposal.action {
+                ProposalAction::SetRerollCooldown(hours) => self.set_reroll_cooldown(hours),
+                ProposalAction::SetUnbondTimeout(hours) => self.set_unbond_timeout(hours),
+                ProposalAction::SetDrawFee(resource_address, draw_fee) => self.set_draw_fee(resource_address, draw_fee),
+                ProposalAction::SetTierWeights(tier_weights) => self.set_tier_weights(tier_weights),
+                ProposalAction::AddTier(tier, weight) => { self.add_tier(tier, weight); }
+            }
+        }
+
+        pub fn get_proposal(&self, proposal_id: u64) -> Proposal {
+            self.proposals.get(&proposal_id).expect("Proposal does not exist").clone()
+        }
+
+        fn tier_details(&self, shard_type: &ShardType) -> (String, Url) {
+            match shard_type {
+                ShardType::Custom(tier_id) => {
+                    let tier = self.custom_tiers.get(tier_id).expect("Unknown tier");
+                    (tier.name.clone(), Url::of(tier.key_image_url.clone()))
+                }
+                _ => (shard_type.name(), shard_type.url())
+            }
+        }
+
+        fn tier_rarity(&self, shard_type: &ShardType) -> u8 {
+            match shard_type {
+                ShardType::Custom(tier_id) => self.custom_tiers.get(tier_id).expect("Unknown tier").rarity,
+                _ => shard_type.rarity()
+            }
+        }
+
+        pub fn set_jackpot_config(&mut self, jackpot_config: JackpotConfig) {
+            assert!(jackpot_config.fee_share >= Decimal::ZERO && jackpot_config.fee_share <= Decimal::ONE, "The fee share must be between 0 and 1");
+            assert!(jackpot_config.payout >= Decimal::ZERO && jackpot_config.payout <= Decimal::ONE, "The payout must be between 0 and 1");
+            assert!(self.tier_weights.iter().any(|(shard_type, weight)| *shard_type == jackpot_config.tier && *weight > 0),
+                "The jackpot tier must have a draw weight"
+            );
+            assert_fee_shares(jackpot_config.fee_share, self.referral_share);
+            self.jackpot_config = jackpot_config;
+        }
+
+        /// Pool that the next win pays from, held in the current draw fee resource
+        pub fn jackpot_amount(&self) -> Decimal {
+            self.jackpot.get(&self.draw_fee_resource).map(|vault| vault.amount()).unwrap_or(Decimal::ZERO)
+        }
+
+        /// Withdraws the pool collected in a former draw fee resource, which wins can no longer pay from
+        pub fn withdraw_jackpot(&mut self, resource_address: ResourceAddress) -> Bucket {
+            assert_ne!(resource_address, self.draw_fee_resource, "The pool of the current draw fee resource can only be won");
+            let mut vault = self.jackpot.get_mut(&resource_address).expect("There is no jackpot pool in this resource");
+            vault.take_all()
+        }
+
+        pub fn jackpot_wins(&self, from: u64, limit: u32) -> Vec<JackpotWin> {
+            (from..self.jackpot_win_counter.min(from + limit as u64))
+                .map(|index| self.jackpot_wins.get(&index).unwrap().clone())
+                .collect()
+        }
+
+        /// Pays the configured share of the jackpot pool held in the current draw fee resource
+        fn pay_jackpot(&mut self, nft_id: &NonFungibleLocalId) -> Bucket {
+            let amount = self.jackpot_amount() * self.jackpot_config.payout;
+            if amount == Decimal::ZERO {
+                return Bucket::new(self.draw_fee_resource);
+            }
+
+            let payout = self.jackpot.get_mut(&self.draw_fee_resource).unwrap()
+                .take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
+            let amount = payout.amount();
+            self.jackpot_wins.insert(self.jackpot_win_counter, JackpotWin {
+                nft_id: nft_id.clone(),
+                resource_address: self.draw_fee_resource,
+                amount,
+                time: Clock::current_time_rounded_to_minutes(),
+            });
+            self.jackpot_win_counter += 1;
+
+            payout
+        }
+
+        pub fn create_profile(&mut self) -> Bucket {
+            let profile_id = NonFungibleLocalId::from(self.profile_counter);
+            self.profile_counter += 1;
+
+            self.shardz_profile.mint_non_fungible(&profile_id, PlayerProfile::new())
+        }
+
+        pub fn set_pity_config(&mut self, pity_config: PityConfig) {
+            self.pity_config = pity_config;
+        }
+
+        /// Draws a ticket bonded with a profile, forcing at least the pity tier once the pity threshold is reached
+        fn draw_with_pity(&self, weights: &[(ShardType, u32)], profile_id: &NonFungibleLocalId, ticket_id: &NonFungibleLocalId, seed: u64) -> ShardType {
+            let profile = self.shardz_profile.get_non_fungible_data::<PlayerProfile>(profile_id);
+            let pity_rarity = self.tier_rarity(&self.pity_config.tier);
+
+            let shard_type = if profile.pity_counter >= self.pity_config.threshold {
+                let rare_weights: Vec<(ShardType, u32)> = weights.iter()
+                    .filter(|(shard_type, weight)| *weight > 0 && self.tier_rarity(shard_type) >= pity_rarity)
+                    .cloned()
+                    .collect();
+
+                if rare_weights.is_empty() {
+                    self.pity_config.tier.clone()
+                } else {
+                    draw_tier(&rare_weights, ticket_id, seed)
+                }
+            } else {
+                draw_tier(weights, ticket_id, seed)
+            };
+
+            let pity_counter = if self.tier_rarity(&shard_type) >= pity_rarity { 0 } else { profile.pity_counter + 1 };
+            self.shardz_profile.update_non_fungible_data(profile_id, "pity_counter", pity_counter);
+
+            shard_type
+        }
+
+        fn checked_profile_id(&self, profile: Option<Proof>) -> Option<NonFungibleLocalId> {
+            profile.map(|profile| {
+                profile.check_with_message(self.shardz_profile.address(), "Incorrect profile")
+                    .as_non_fungible()
+                    .non_fungible_local_id()
+            })
+        }
+
+        /// Adds bonded tickets, pulled shards and rerolls to the lifetime stats of a profile and unlocks achievements
+        fn record_profile_activity(&self, profile_id: &NonFungibleLocalId, tickets_bonded: u64, pulls: &[ShardType], rerolls: u64) {
+            let mut profile = self.shardz_profile.get_non_fungible_data::<PlayerProfile>(profile_id);
+
+            if tickets_bonded > 0 {
+                profile.tickets_bonded += tickets_bonded;
+                self.shardz_profile.update_non_fungible_data(profile_id, "tickets_bonded", profile.tickets_bonded);
+            }
+            if !pulls.is_empty() {
+                for shard_type in pulls {
+                    *profile.shards_pulled.entry(shard_type.clone()).or_insert(0) += 1;
+                }
+                self.shardz_profile.update_non_fungible_data(profile_id, "shards_pulled", profile.shards_pulled.clone());
+            }
+            if rerolls > 0 {
+                profile.rerolls += rerolls;
+                self.shardz_profile.update_non_fungible_data(profile_id, "rerolls", profile.rerolls);
+            }
+
+            let new_achievements = profile.new_achievements();
+            if !new_achievements.is_empty() {
+                profile.achievements.extend(new_achievements);
+                self.shardz_profile.update_non_fungible_data(profile_id, "achievements", profile.achievements);
+            }
+        }
+
+        pub fn schedule_season(&mut self, name: String, start: Instant, end: Instant, tiers: Vec<(CustomTier, u32)>) -> u64 {
+            assert!(start < end, "A season must end after its start");
+            assert!(!Clock::current_time_is_at_or_after(end, TimePrecision::Minute), "A season cannot end in the past");
+
+            let season_id = self.season_counter;
+            let mut tier_weights: Vec<(ShardType, u32)> = Vec::new();
+            for (tier, weight) in tiers {
+                let shard_type = self.register_tier(tier);
+                if let ShardType::Custom(tier_id) = shard_type {
+                    self.season_tiers.insert(tier_id, season_id);
+                }
+                tier_weights.push((shard_type, weight));
+            }
+
+            self.seasons.insert(season_id, Season {
+                name,
+                start,
+                end,
+                tier_weights,
+            });
+            self.scheduled_seasons.push(season_id);
+            self.season_counter += 1;
+
+            season_id
+        }
+
+        pub fn get_season(&self, season_id: u64) -> Season {
+            self.seasons.get(&season_id).expect("Season does not exist").clone()
+        }
+
+        fn season_of(&self, shard_type: &ShardType) -> Option<u64> {
+            match shard_type {
+                ShardType::Custom(tier_id) => self.season_tiers.get(tier_id).map(|season_id| *season_id),
+                _ => None,
+            }
+        }
+
+        /// Season tiers can only be minted until the end of their season, even from tickets drawn during the season
+        fn assert_season_not_over(&self, season_id: u64) {
+            assert!(!self.season_is_over(season_id), "Season {} is over, its tiers cannot be minted anymore", season_id);
+        }
+
+        fn season_is_over(&self, season_id: u64) -> bool {
+            let end = self.seasons.get(&season_id).unwrap().end;
+            Clock::current_time_is_at_or_after(end, TimePrecision::Minute)
+        }
+
+        fn register_tier(&mut self, tier: CustomTier) -> ShardType {
+            assert_tier_rarity(&tier);
+            let shard_type = ShardType::Custom(self.custom_tier_counter);
+            self.custom_tiers.insert(self.custom_tier_counter, tier);
+            self.custom_tier_counter += 1;
+
+            shard_type
+        }
+
+        /// Base tier weights extended with the tiers of the seasons currently running
+        fn current_weights(&mut self) -> Vec<(ShardType, u32)> {
+            let mut weights = self.tier_weights.clone();
+            let mut scheduled_seasons: Vec<u64> = Vec::new();
+
+            for season_id in self.scheduled_seasons.iter() {
+                let season = self.seasons.get(season_id).unwrap();
+
+                // Ended seasons are dropped for good so that their tiers can never be drawn again
+                if Clock::current_time_is_at_or_after(season.end, TimePrecision::Minute) {
+                    continue;
+                }
+                if Clock::current_time_is_at_or_after(season.start, TimePrecision::Minute) {
+                    weights.extend(season.tier_weights.iter().cloned());
+                }
+                scheduled_seasons.push(*season_id);
+            }
+            self.scheduled_seasons = scheduled_seasons;
+
+            weights
+        }
+
+        pub fn set_launch_phases(&mut self, launch_phases: Vec<LaunchPhase>) {
+            for phases in launch_phases.windows(2) {
+                assert!(phases[0].start < phases[1].start, "Launch phases must be sorted by start time");
+            }
+            self.launch_phases = launch_phases;
+        }
+
+        pub fn add_to_allowlist(&mut self, phase_index: u32, members: Vec<NonFungibleGlobalId>) {
+            for member in members {
+                self.allowlists.insert((phase_index, member), ());
+            }
+        }
+
+        pub fn remove_from_allowlist(&mut self, phase_index: u32, members: Vec<NonFungibleGlobalId>) {
+            for member in members {
+                self.allowlists.remove(&(phase_index, member));
+            }
+        }
+
+        pub fn current_phase(&self) -> Option<LaunchPhase> {
+            self.current_phase_index().map(|phase_index| self.launch_phases[phase_index as usize].clone())
+        }
+
+        /// Index of the last launch phase that started, if any
+        fn current_phase_index(&self) -> Option<u32> {
+            self.launch_phases.iter()
+                .rposition(|phase| Clock::current_time_is_at_or_after(phase.start, TimePrecision::Minute))
+                .map(|phase_index| phase_index as u32)
+        }
+
+        fn check_phase_access(&mut self, phase_index: u32, badge: Option<&CheckedProof>, ticket_count: u64) {
+            let phase = self.launch_phases[phase_index as usize].clone();
+
+            let member = match phase.access {
+                PhaseAccess::Public => return,
+                PhaseAccess::Badge(badge_address) => {
+                    let badge = badge.expect("A badge is required during this launch phase");
+                    assert_eq!(badge.resource_address(), badge_address, "Incorrect badge for this launch phase");
+                    NonFungibleGlobalId::new(badge_address, badge.as_non_fungible().non_fungible_local_id())
+                }
+                PhaseAccess::Allowlist => {
+                    let badge = badge.expect("A badge is required during this launch phase");
+                    let member = NonFungibleGlobalId::new(badge.resource_address(), badge.as_non_fungible().non_fungible_local_id());
+                    assert!(self.allowlists.get(&(phase_index, member.clone())).is_some(), "Badge is not on the allowlist of this launch phase");
+                    member
+                }
+            };
+
+            // Tickets bonded during the phase are counted per badge
+            let minted = self.phase_mints.get(&(phase_index, member.clone())).map(|minted| *minted).unwrap_or(0) + ticket_count;
+            if let Some(ticket_cap) = phase.ticket_cap {
+                assert!(minted <= ticket_cap, "Only {} tickets can be bonded per badge during this launch phase", ticket_cap);
+            }
+            self.phase_mints.insert((phase_index, member), minted);
+        }
+
+        pub fn set_referral_share(&mut self, referral_share: Decimal) {
+            assert!(referral_share >= Decimal::ZERO && referral_share <= Decimal::ONE, "The referral share must be between 0 and 1");
+            assert_fee_shares(self.jackpot_config.fee_share, referral_share);
+            self.referral_share = referral_share;
+        }
+
+        pub fn referral_rewards(&self, referrer: Referrer, resource_address: ResourceAddress) -> Decimal {
+            self.referral_rewards.get(&(referrer, resource_address)).map(|vault| vault.amount()).unwrap_or(Decimal::ZERO)
+        }
+
+        pub fn claim_referral_rewards(&mut self, profile: Proof, resource_address: ResourceAddress) -> Bucket {
+            let referrer = Referrer::Profile(self.checked_profile_id(Some(profile)).unwrap());
+
+            self.referral_rewards.get_mut(&(referrer, resource_address))
+                .expect("No referral rewards to claim")
+                .take_all()
+        }
+
+        /// Burns the given shards to airdrop tickets, either undrawn or already drawn as the given tier
+        pub fn airdrop_tickets(&mut self, mut shards: Bucket, recipients: Vec<(ComponentAddress, u32)>, shard_type: Option<ShardType>) -> Bucket {
+            assert_eq!(shards.resource_address(), self.shardz_fungible.address(), "Incorrect resource address");
+            if let Some(shard_type) = &shard_type {
+                self.assert_airdroppable(shard_type);
+            }
+            let ticket_count: u64 = recipients.iter().map(|(_, count)| *count as u64).sum();
+            assert!(shards.amount() >= Decimal::from(ticket_count), "Not enough shards to airdrop {} tickets", ticket_count);
+            shards.take(ticket_count).burn();
+
+            for (account_address, count) in recipients {
+                let tickets = self.mint_tickets(count as u64, shard_type.clone(), None, None);
+                self.deposit_or_keep(account_address, tickets);
+            }
+
+            shards
+        }
+
+        /// Tickets can only be airdropped already drawn as a tier that the current weights can draw, other than the jackpot tier
+        fn assert_airdroppable(&mut self, shard_type: &ShardType) {
+            if let ShardType::Custom(tier_id) = shard_type {
+                assert!(self.custom_tiers.get(tier_id).is_some(), "Unknown tier {}", tier_id);
+            }
+            if let Some(season_id) = self.season_of(shard_type) {
+                self.assert_season_not_over(season_id);
+            }
+            assert!(*shard_type != self.jackpot_config.tier, "The jackpot tier can only be won through draws");
+
+            let drawable = self.current_weights().iter().any(|(tier, weight)| tier == shard_type && *weight > 0);
+            assert!(drawable, "Tier {:?} cannot be drawn with the current weights", shard_type);
+        }
+
+        pub fn airdrop_shards(&mut self, mut shards: Bucket, recipients: Vec<(ComponentAddress, Decimal)>) -> Bucket {
+            assert_eq!(shards.resource_address(), self.shardz_fungible.address(), "Incorrect resource address");
+
+            for (account_address, amount) in recipients {
+                let airdrop = shards.take(amount);
+                self.deposit_or_keep(account_address, airdrop);
+            }
+
+            shards
+        }
+
+        pub fn unclaimed_airdrop(&self, account_address: ComponentAddress, resource_address: ResourceAddress) -> Decimal {
+            self.unclaimed_airdrops.get(&(account_address, resource_address)).map(|vault| vault.amount()).unwrap_or(Decimal::ZERO)
+        }
+
+        /// Deposits an airdrop that was refused by an account, once it accepts the deposit
+        pub fn claim_airdrop(&mut self, account_address: ComponentAddress, resource_address: ResourceAddress) {
+            let airdrop = self.unclaimed_airdrops.get_mut(&(account_address, resource_address))
+                .expect("No airdrop to claim")
+                .take_all();
+
+            let mut account: Global<Account> = Global::from(account_address);
+            account.try_deposit_or_abort(airdrop, None);
+        }
+
+        fn deposit_or_keep(&mut self, account_address: ComponentAddress, bucket: Bucket) {
+            let mut account: Global<Account> = Global::from(account_address);
+
+            if let Some(refund) = account.try_deposit_or_refund(bucket, None) {
+                put_in_vault(&self.unclaimed_airdrops, (account_address, refund.resource_address()), refund);
+            }
+        }
+
+        /// Mints tickets, undrawn tickets are added to the pending queue
+        fn mint_tickets(&mut self, count: u64, shard_type: Option<ShardType>, profile_id: Option<NonFungibleLocalId>, treasury_fee: Option<(ResourceAddress, Decimal)>) -> Bucket {
+            let bond_time = Clock::current_time_rounded_to_minutes();
+            let expires_at = hours_after(bond_time, self.ticket_lifetime_hours);
+            let bond_hash = Runtime::transaction_hash();
+            let mut entries: IndexMap<NonFungibleLocalId, ShardTicket> = index_map_new();
+
+            for _ in 0..count {
+                let nft_id = NonFungibleLocalId::from(self.ticket_counter);
+
+                let data = ShardTicket {
+                    shard_type: shard_type.clone(),
+                    bond_time,
+                    profile_id: profile_id.clone(),
+                    expires_at,
+                    treasury_fee,
+                    seed_commitment: self.seed_commitment,
+                    bond_hash,
+                };
+
+                entries.insert(nft_id.clone(), data);
+                if shard_type.is_none() {
+                    self.pending_queue.insert(self.pending_tail, nft_id);
+                    self.pending_tail += 1;
+                    self.undrawn_tickets += 1;
+                }
+                self.ticket_counter += 1;
+            }
+
+            let ticket_ids: Vec<NonFungibleLocalId> = entries.keys().cloned().collect();
+            let ticket_bucket = mint_in_bulk(&self.shardz_ticket, entries);
+            Runtime::emit_event(TicketsMintedEvent { ticket_ids, shard_type });
+
+            ticket_bucket
+        }
+
+        pub fn list_for_rent(&mut self, shard: Bucket, payment_resource: ResourceAddress, price_per_hour: Decimal, max_hours: u32) -> Bucket {
+            assert_eq!(shard.resource_address(), self.shardz_nft.address(), "Incorrect resource address");
+            assert!(price_per_hour >= Decimal::ZERO, "The price cannot be negative");
+
+            let shard_id = shard.as_non_fungible().non_fungible_local_id();
+            let shard_type = self.shardz_nft.get_non_fungible_data::<ShardNFT>(&shard_id).shard_type;
+
+            self.rental_escrow.put(shard);
+            self.rental_listings.insert(shard_id.clone(), RentalListing {
+                payment_resource,
+                price_per_hour,
+                max_hours,
+                rented_until: None,
+            });
+
+            let receipt_id = NonFungibleLocalId::from(self.rental_receipt_counter);
+            self.rental_receipt_counter += 1;
+
+            self.shardz_rental_receipt.mint_non_fungible(&receipt_id, RentalReceipt { shard_id, shard_type })
+        }
+
+        pub fn rent(&mut self, shard_id: NonFungibleLocalId, hours: u32, mut payment: Bucket) -> (Bucket, Bucket) {
+            let mut listing = self.rental_listings.get_mut(&shard_id).expect("Shard is not listed for rent");
+
+            if let Some(rented_until) = listing.rented_until {
+                assert!(Clock::current_time_is_at_or_after(rented_until, TimePrecision::Minute), "Shard is already rented");
+            }
+            assert!(hours > 0 && hours <= listing.max_hours, "Shard can be rented for at most {} hours", listing.max_hours);
+            assert_eq!(payment.resource_address(), listing.payment_resource, "Incorrect payment resource");
+
+            let price = listing.price_per_hour * hours;
+            assert!(payment.amount() >= price, "Renting this shard for {} hours costs {}", hours, price);
+
+            let expires_at = hours_after(Clock::current_time_rounded_to_minutes(), hours);
+            listing.rented_until = Some(expires_at);
+            drop(listing);
+
+            put_in_vault(&self.rental_earnings, shard_id.clone(), payment.take(price));
+
+            let shard_type = self.shardz_nft.get_non_fungible_data::<ShardNFT>(&shard_id).shard_type;
+            let pass_id = NonFungibleLocalId::from(self.rental_pass_counter);
+            self.rental_pass_counter += 1;
+
+            let pass = self.shardz_rental_pass.mint_non_fungible(&pass_id, RentalPass {
+                shard_id,
+                shard_type,
+                expires_at,
+            });
+
+            (pass, payment)
+        }
+
+        /// Gives the shard and its rental earnings back to the owner, once the current rental is over
+        pub fn reclaim_rental(&mut self, receipt: Bucket) -> (Bucket, Option<Bucket>) {
+            assert_eq!(receipt.resource_address(), self.shardz_rental_receipt.address(), "Incorrect resource address");
+
+            let shard_id = receipt.as_non_fungible().non_fungible::<RentalReceipt>().data().shard_id;
+            let listing = self.rental_listings.remove(&shard_id).unwrap();
+            if let Some(rented_until) = listing.rented_until {
+                assert!(Clock::current_time_is_at_or_after(rented_until, TimePrecision::Minute), "Shard is still rented");
+            }
+
+            receipt.burn();
+            let shard: Bucket = self.rental_escrow.as_non_fungible().take_non_fungible(&shard_id).into();
+            let earnings = self.rental_earnings.get_mut(&shard_id).map(|mut vault| vault.take_all());
+
+            (shard, earnings)
+        }
+
+        pub fn get_rental_listing(&self, shard_id: NonFungibleLocalId) -> RentalListing {
+            self.rental_listings.get(&shard_id).expect("Shard is not listed for rent").clone()
+        }
+
+        /// Shards proven by a proof of shard NFTs or of valid rental passes, with their tiers
+        fn proven_shards(&self, proof: Proof) -> Vec<(NonFungibleLocalId, ShardType)> {
+            let proof = proof.skip_checking();
+
+            if proof.resource_address() == self.shardz_nft.address() {
+                proof.as_non_fungible().non_fungibles::<ShardNFT>().into_iter()
+                    .map(|shard| (shard.local_id().clone(), shard.data().shard_type))
+                    .collect()
+            } else if proof.resource_address() == self.shardz_rental_pass.address() {
+                proof.as_non_fungible().non_fungibles::<RentalPass>().into_iter()
+                    .map(|pass| {
+                        let pass = pass.data();
+                        assert!(!Clock::current_time_is_at_or_after(pass.expires_at, TimePrecision::Minute), "Rental pass has expired");
+                        (pass.shard_id, pass.shard_type)
+                    })
+                    .collect()
+            } else {
+                panic!("Incorrect proof")
+            }
+        }
+
+        pub fn banked_dust(&self, profile_id: NonFungibleLocalId) -> Decimal {
+            self.dust.get(&profile_id).map_or(Decimal::ZERO, |dust| dust.amount())
+        }
+
+        pub fn withdraw_dust(&mut self, profile: Proof) -> Bucket {
+            let profile_id = self.checked_profile_id(Some(profile)).unwrap();
+            let mut dust = self.dust.get_mut(&profile_id).expect("No dust banked for this profile");
+            dust.take_all()
+        }
+
+        pub fn set_ticket_lifetime(&mut self, hours: u32) {
+            self.ticket_lifetime_hours = hours;
+        }
+
+        pub fn set_keeper_tip(&mut self, keeper_tip: Decimal) {
+            assert!(keeper_tip >= Decimal::ZERO, "The keeper tip cannot be negative");
+            self.keeper_tip = keeper_tip;
+        }
+
+        /// Converts expired tickets back to SHARD, drawn or not, and tips the caller from the treasury for each ticket
+        /// whose draw fee was paid in the current fee resource
+        pub fn recycle_expired(&mut self, ticket_bucket: Bucket) -> (Bucket, Bucket) {
+            assert_eq!(ticket_bucket.resource_address(), self.shardz_ticket.address(), "Incorrect resource address");
+            assert!(ticket_bucket.amount() <= Decimal::from(MAX_ITEMS_PER_CALL as u64),
+                "At most {} tickets can be recycled per call", MAX_ITEMS_PER_CALL
+            );
+
+            let mut ticket_ids: Vec<NonFungibleLocalId> = Vec::new();
+            let mut tip = Decimal::ZERO;
+            for nft_ticket in ticket_bucket.as_non_fungible().non_fungibles::<ShardTicket>() {
+                let ticket = nft_ticket.data();
+                assert!(Clock::current_time_is_at_or_after(ticket.expires_at, TimePrecision::Minute),
+                    "Ticket {} has not expired yet", nft_ticket.local_id()
+                );
+                if ticket.shard_type.is_none() {
+                    self.undrawn_tickets -= 1;
+                }
+
+                // Only holders can recycle their tickets, so the tip is a refund of at most what the treasury kept
+                // from the fee of the ticket, and recycling cannot drain the treasury
+                if let Some((resource_address, treasury_fee)) = ticket.treasury_fee {
+                    if resource_address == self.draw_fee_resource {
+                        tip += self.keeper_tip.min(treasury_fee);
+                    }
+                }
+                ticket_ids.push(nft_ticket.local_id().clone());
+            }
+
+            // The tip is capped by what the treasury holds
+            let tip_bucket = match self.treasury.get_mut(&self.draw_fee_resource) {
+                Some(mut vault) => {
+                    let amount = tip.min(vault.amount());
+                    vault.take(amount)
+                }
+                None => Bucket::new(self.draw_fee_resource),
+            };
+
+            let fungible_bucket = self.shardz_fungible.mint(ticket_bucket.amount());
+            ticket_bucket.burn();
+            Runtime::emit_event(TicketsRecycledEvent { ticket_ids });
+
+            (fungible_bucket, tip_bucket)
+        }
+
+        /// Caps the tickets minted by a single bond so that the transaction stays within the cost unit limit
+        pub fn set_max_tickets_per_bond(&mut self, max_tickets_per_bond: u64) {
+            assert!(max_tickets_per_bond > 0, "At least one ticket must be bondable per call");
+            self.max_tickets_per_bond = max_tickets_per_bond;
+        }
+
+        /// Stops bonding, swapping and rerolling, unbonding stays open so that players can always leave
+        pub fn set_paused(&mut self, paused: bool) {
+            self.paused = paused;
+        }
+
+        pub fn stats(&self) -> ShardzStats {
+            ShardzStats {
+                tickets_minted: self.ticket_counter - 1,
+                shards_minted: self.nft_counter - 1,
+                pending_tickets: self.undrawn_tickets,
+                shards_in_circulation: self.shardz_fungible.total_supply().unwrap(),
+                tickets_in_circulation: self.shardz_ticket.total_supply().unwrap(),
+                nfts_in_circulation: self.shardz_nft.total_supply().unwrap(),
+                paused: self.paused,
+            }
+        }
+
+        pub fn set_loan_config(&mut self, loan_config: LoanConfig) {
+            assert!(loan_config.interest_per_hour >= Decimal::ZERO, "The interest cannot be negative");
+            self.loan_config = loan_config;
+        }
+
+        pub fn supply_liquidity(&mut self, shards: Bucket) -> Bucket {
+            assert_eq!(shards.resource_address(), self.shardz_fungible.address(), "Incorrect resource address");
+
+            let pool_value = self.lending_pool_value();
+            let total_units = self.shardz_pool_unit.total_supply().unwrap();
+            let units = if total_units == Decimal::ZERO || pool_value == Decimal::ZERO {
+                shards.amount()
+            } else {
+                shards.amount() * total_units / pool_value
+            };
+
+            self.lending_pool.put(shards);
+            self.shardz_pool_unit.mint(units)
+        }
+
+        pub fn withdraw_liquidity(&mut self, units: Bucket) -> Bucket {
+            assert_eq!(units.resource_address(), self.shardz_pool_unit.address(), "Incorrect resource address");
+
+            let amount = units.amount() * self.lending_pool_value() / self.shardz_pool_unit.total_supply().unwrap();
+            assert!(amount <= self.lending_pool.amount(), "Not enough liquidity in the lending pool");
+            units.burn();
+
+            self.lending_pool.take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero))
+        }
+
+        /// Value of the lending pool in SHARD: available liquidity, loans principal and liquidated collateral
+        pub fn lending_pool_value(&self) -> Decimal {
+            self.lending_pool.amount() + self.outstanding_principal + self.liquidated_value
+        }
+
+        pub fn borrow(&mut self, collateral: Bucket) -> (Bucket, Bucket) {
+            assert_eq!(collateral.resource_address(), self.shardz_nft.address(), "Incorrect resource address");
+
+            let shard_id = collateral.as_non_fungible().non_fungible_local_id();
+            let shard_type = self.shardz_nft.get_non_fungible_data::<ShardNFT>(&shard_id).shard_type;
+            let principal = self.loan_config.tier_ltv.iter()
+                .find(|(tier, _)| *tier == shard_type)
+                .map(|(_, ltv)| *ltv)
+                .expect("This tier cannot be used as collateral");
+            assert!(principal <= self.lending_pool.amount(), "Not enough liquidity in the lending pool");
+
+            let start = Clock::current_time_rounded_to_minutes();
+            let deadline = hours_after(start, self.loan_config.duration_hours);
+            let receipt_id = NonFungibleLocalId::from(self.loan_receipt_counter);
+            self.loan_receipt_counter += 1;
+
+            self.loan_collateral.put(collateral);
+            self.loans.insert(shard_id.clone(), Loan {
+                receipt_id: receipt_id.clone(),
+                shard_type: shard_type.clone(),
+                principal,
+                start,
+                deadline,
+            });
+            self.outstanding_principal += principal;
+
+            let receipt = self.shardz_loan_receipt.mint_non_fungible(&receipt_id, LoanReceipt {
+                shard_id,
+                shard_type,
+                principal,
+                deadline,
+            });
+
+            (self.lending_pool.take(principal), receipt)
+        }
+
+        pub fn repay(&mut self, receipt: Bucket, mut payment: Bucket) -> (Bucket, Bucket) {
+            assert_eq!(receipt.resource_address(), self.shardz_loan_receipt.address(), "Incorrect resource address");
+            assert_eq!(payment.resource_address(), self.shardz_fungible.address(), "Incorrect payment resource");
+
+            let receipt_nft = receipt.as_non_fungible().non_fungible::<LoanReceipt>();
+            let shard_id = receipt_nft.data().shard_id;
+            // A liquidated loan leaves its receipt behind, and the shard may back a new loan since
+            let loan = self.loans.get(&shard_id)
+                .filter(|loan| loan.receipt_id == *receipt_nft.local_id())
+                .expect("Loan was liquidated")
+                .clone();
+            assert!(!Clock::current_time_is_at_or_after(loan.deadline, TimePrecision::Minute), "Loan is past its deadline");
+
+            let owed = loan.owed(self.loan_config.interest_per_hour, Clock::current_time_rounded_to_minutes());
+            assert!(payment.amount() >= owed, "{} SHARD are owed on this loan", owed);
+
+            self.lending_pool.put(payment.take(owed));
+            self.outstanding_principal -= loan.principal;
+            self.loans.remove(&shard_id);
+            receipt.burn();
+
+            let shard: Bucket = self.loan_collateral.as_non_fungible().take_non_fungible(&shard_id).into();
+
+            (shard, payment)
+        }
+
+        pub fn loan_owed(&self, shard_id: NonFungibleLocalId) -> Decimal {
+            let loan = self.loans.get(&shard_id).expect("Loan does not exist");
+            loan.owed(self.loan_config.interest_per_hour, Clock::current_time_rounded_to_minutes())
+        }
+
+        /// Moves the collateral of a loan past its deadline to the lending pool
+        pub fn liquidate(&mut self, shard_id: NonFungibleLocalId) {
+            let loan = self.loans.remove(&shard_id).expect("Loan does not exist");
+            assert!(Clock::current_time_is_at_or_after(loan.deadline, TimePrecision::Minute), "Loan is not past its deadline");
+
+            let value = loan.owed(self.loan_config.interest_per_hour, loan.deadline);
+            let shard = self.loan_collateral.as_non_fungible().take_non_fungible(&shard_id);
+
+            self.liquidated_shards.put(shard.into());
+            self.liquidated_values.insert(shard_id, value);
+            self.outstanding_principal -= loan.principal;
+            self.liquidated_value += value;
+        }
+
+        /// Trades pool units worth the value of a liquidated shard for the shard
+        pub fn claim_liquidated_shard(&mut self, mut units: Bucket, shard_id: NonFungibleLocalId) -> (Bucket, Bucket) {
+            assert_eq!(units.resource_address(), self.shardz_pool_unit.address(), "Incorrect resource address");
+
+            let value = *self.liquidated_values.get(&shard_id).expect("Shard was not liquidated");
+            let units_needed = value * self.shardz_pool_unit.total_supply().unwrap() / self.lending_pool_value();
+            assert!(units.amount() >= units_needed, "{} pool units are needed to claim this shard", units_needed);
+
+            units.take_advanced(units_needed, WithdrawStrategy::Rounded(RoundingMode::AwayFromZero)).burn();
+            self.liquidated_values.remove(&shard_id);
+            self.liquidated_value -= value;
+
+            let shard: Bucket = self.liquidated_shards.as_non_fungible().take_non_fungible(&shard_id).into();
+
+            (shard, units)
+        }
     }
 }
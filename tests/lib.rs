@@ -1,8 +1,10 @@
 #[cfg(test)]
 mod shardz_tests {
+    use proptest::prelude::*;
     use test_engine::prelude::*;
 
-    use radix_shardz::shardz::{ShardNFT, ShardTicket, ShardType};
+    use radix_shardz::governance::{GovernanceConfig, Proposal, ProposalAction};
+    use radix_shardz::shardz::{default_tier_weights, draw_tier, seed_commitment, ticket_seed, whole_units, Achievement, CustomTier, JackpotConfig, JackpotWin, LaunchPhase, PhaseAccess, PityConfig, PlayerProfile, Referrer, LoanReceipt, RentalPass, RentalReceipt, Season, SeedChain, ShardNFT, ShardTicket, ShardType, ShardzStats};
 
     global_package!(SHARDZ, ".");
 
@@ -23,11 +25,68 @@ mod shardz_tests {
 
         test_engine.add_global_package("shardz package", &SHARDZ);
 
-        test_engine.new_component("shards comp", "Shardz", "instantiate_shardz", env_args!(*test_engine.current_account_address()));
+        test_engine.new_component("shards comp", "Shardz", "instantiate_shardz", env_args!(*test_engine.current_account_address(), seed_chain().head()));
 
         test_engine
     }
 
+    /// Seed chain of the drawer in the tests
+    fn seed_chain() -> SeedChain {
+        SeedChain::new([7; 32], 1000)
+    }
+
+    /// Seed revealing the current commitment of the component
+    fn next_reveal(test_engine: &mut TestEngine) -> Hash {
+        let commitment: Hash = test_engine.call_method("seed_commitment", env_args!()).get_return();
+        seed_chain().reveal(&commitment).unwrap()
+    }
+
+    /// Draws a ticket with a forced outcome by temporarily restricting the draw weights to a single tier
+    pub fn draw_ticket(test_engine: &mut TestEngine, ticket_id: u64, shard_type: ShardType) {
+        test_engine.call_method_builder("set_tier_weights", env_args!(vec![(shard_type, 1u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        let reveal = next_reveal(test_engine);
+        test_engine.call_method_builder("draw_batch", env_args!(vec![NonFungibleLocalId::integer(ticket_id)], Some(reveal)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+    }
+
+    /// Probabilities of mint published in the README, out of 10000
+    const PUBLISHED_ODDS: [(ShardType, u32); 6] = [
+        (ShardType::Clear, 3800),
+        (ShardType::Yellow, 2800),
+        (ShardType::Orange, 2000),
+        (ShardType::Blue, 1000),
+        (ShardType::Scrypto, 300),
+        (ShardType::Radix, 100),
+    ];
+
+    /// Critical value of the chi-square distribution with 5 degrees of freedom (6 tiers) at a 0.1% significance level
+    const CHI_SQUARE_CRITICAL_5: f64 = 20.515;
+
+    /// Pearson's chi-square statistic of the observed tier counts against the expected weights.
+    /// Drawing a tier that has no expected weight is an infinite deviation.
+    fn chi_square(weights: &[(ShardType, u32)], draws: &[ShardType]) -> f64 {
+        let total_weight: u32 = weights.iter().map(|(_, weight)| weight).sum();
+
+        if draws.iter().any(|draw| !weights.iter().any(|(tier, weight)| tier == draw && *weight > 0)) {
+            return f64::INFINITY;
+        }
+
+        weights.iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(tier, weight)| {
+                let expected = draws.len() as f64 * *weight as f64 / total_weight as f64;
+                let observed = draws.iter().filter(|draw| *draw == tier).count() as f64;
+                (observed - expected).powi(2) / expected
+            })
+            .sum()
+    }
+
     #[test]
     fn test_instantiation() {
         let mut test_engine = instantiate();
@@ -45,7 +104,7 @@ mod shardz_tests {
     fn test_bond() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23"))))
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
             .expect_commit_success();
 
         assert_eq!(test_engine.current_balance("Shard"), dec!(997));
@@ -58,7 +117,7 @@ mod shardz_tests {
 
         for i in 1..4 {
             let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", i);
-            assert_eq!(ticket_data, ShardTicket{shard_type: None})
+            assert_eq!(ticket_data.shard_type, None)
         }
     }
 
@@ -66,26 +125,132 @@ mod shardz_tests {
     fn test_bond_xrd_fails() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("xrd", dec!("3.23")))).assert_failed_with("Incorrect resource address");
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("xrd", dec!("3.23")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>)).assert_failed_with("Incorrect resource address");
     }
 
     #[test]
-    fn test_set_ticket_data() {
+    fn test_draw_batch() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23"))));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
 
-        test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Blue)), "admin badge").expect_commit_success();
+        let reveal = next_reveal(&mut test_engine);
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1, 3], Some(reveal)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
 
-        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 1);
-        assert_eq!(ticket_data, ShardTicket{shard_type: Some(ShardType::Blue)});
+        // Every draw can be checked from the revealed seed and the bond transaction
+        let weights = default_tier_weights();
+        for ticket_id in [1u64, 3] {
+            let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", ticket_id);
+            assert_eq!(seed_commitment(&reveal), ticket_data.seed_commitment);
+            let seed = ticket_seed(&reveal, &ticket_data.bond_hash);
+            assert_eq!(ticket_data.shard_type, Some(draw_tier(&weights, &NonFungibleLocalId::integer(ticket_id), seed)));
+        }
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert_eq!(pending, nf_ids![2]);
+    }
+
+    #[test]
+    fn test_draw_batch_twice_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 1, ShardType::Blue);
+
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1], None::<Hash>))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("Ticket #1# was already drawn");
+    }
+
+    #[test]
+    fn test_draw_requires_revealed_seed() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        // The drawer cannot pick another seed than the one it committed to before the tickets were bonded
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1], Some(Hash([1; 32]))))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The revealed seed does not match the seed commitment");
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1], None::<Hash>))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The seed of ticket #1# has not been revealed");
+
+        // Revealing the seed makes it the commitment of the next tickets
+        let reveal = next_reveal(&mut test_engine);
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1], Some(reveal)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        let commitment: Hash = test_engine.call_method("seed_commitment", env_args!()).get_return();
+        assert_eq!(commitment, reveal);
+
+        // Ticket 2 was bonded with the revealed commitment, so it can be drawn without a new reveal but not unbonded
+        test_engine.advance_time(3600*24*1000);
+        test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![2])))
+            .assert_failed_with("The seed of ticket #2# was revealed, it can only be drawn");
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![2], None::<Hash>))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+    }
+
+    #[test]
+    fn test_commit_new_seed_chain() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        let new_chain = SeedChain::new([8; 32], 10);
+        test_engine.call_method("commit_seed", env_args!(new_chain.head())).assert_failed_with("");
+        test_engine.call_method_builder("commit_seed", env_args!(new_chain.head()))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("commit_seed", env_args!(new_chain.head()))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("This seed commitment was already used");
+
+        // Ticket 1 is still drawn with the seed of the old chain, the new chain reveals its own commitment
+        test_engine.call_method_builder("draw_batch", env_args!(nf_ids![1], Some(new_chain.reveal(&new_chain.head()).unwrap())))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The seed of ticket #1# has not been revealed");
+    }
+
+    #[test]
+    fn test_random_cannot_draw() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+
+        let reveal = next_reveal(&mut test_engine);
+        test_engine.call_method("draw_batch", env_args!(nf_ids![1], Some(reveal))).assert_failed_with("");
+    }
+
+    #[test]
+    fn test_badge_cannot_set_ticket_data() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+
+        test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Blue)), "admin badge").assert_failed_with("");
     }
 
     #[test]
     fn test_random_cannot_set() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23"))));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("3.23")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
 
         test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Blue)), "xrd").assert_failed_with("");
     }
@@ -95,20 +260,20 @@ mod shardz_tests {
     fn test_all_swap_combinations() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8)));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
 
-        test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Clear)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 2, "shard_type", env_args!(Some(ShardType::Yellow)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 3, "shard_type", env_args!(Some(ShardType::Orange)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 4, "shard_type", env_args!(Some(ShardType::Blue)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 5, "shard_type", env_args!(Some(ShardType::Emerald)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 6, "shard_type", env_args!(Some(ShardType::Scrypto)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 7, "shard_type", env_args!(Some(ShardType::Radix)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 8, "shard_type", env_args!(Some(ShardType::Xian)), "admin badge").expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+        draw_ticket(&mut test_engine, 3, ShardType::Orange);
+        draw_ticket(&mut test_engine, 4, ShardType::Blue);
+        draw_ticket(&mut test_engine, 5, ShardType::Emerald);
+        draw_ticket(&mut test_engine, 6, ShardType::Scrypto);
+        draw_ticket(&mut test_engine, 7, ShardType::Radix);
+        draw_ticket(&mut test_engine, 8, ShardType::Xian);
 
         let ids= nf_ids![1, 2, 3, 4, 5, 6, 7, 8];
 
-        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()))).expect_commit_success();
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()), None::<ManifestProof>)).expect_commit_success();
 
         let nft_ticket_owned = test_engine.current_ids_balance("Shard Ticket");
         let mut nft_owned = test_engine.current_ids_balance("Shard NFT");
@@ -142,44 +307,44 @@ mod shardz_tests {
     fn test_all_swap_combination_and_destroy_fails_reroll() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8)));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
 
-        test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Clear)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 2, "shard_type", env_args!(Some(ShardType::Yellow)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 3, "shard_type", env_args!(Some(ShardType::Orange)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 4, "shard_type", env_args!(Some(ShardType::Blue)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 5, "shard_type", env_args!(Some(ShardType::Emerald)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 6, "shard_type", env_args!(Some(ShardType::Scrypto)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 7, "shard_type", env_args!(Some(ShardType::Radix)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 8, "shard_type", env_args!(Some(ShardType::Xian)), "admin badge").expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+        draw_ticket(&mut test_engine, 3, ShardType::Orange);
+        draw_ticket(&mut test_engine, 4, ShardType::Blue);
+        draw_ticket(&mut test_engine, 5, ShardType::Emerald);
+        draw_ticket(&mut test_engine, 6, ShardType::Scrypto);
+        draw_ticket(&mut test_engine, 7, ShardType::Radix);
+        draw_ticket(&mut test_engine, 8, ShardType::Xian);
 
         let ids= nf_ids![1, 2, 3, 4, 5, 6, 7, 8];
-        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()))).expect_commit_success();
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()), None::<ManifestProof>)).expect_commit_success();
 
-        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", ids))).assert_failed_with("There is a 4 hour delay between minting and rerolling");
+        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", ids), None::<ManifestProof>)).assert_failed_with("There is a 4 hour delay between minting and rerolling");
     }
 
     #[test]
     fn test_all_swap_combination_and_destroy() {
         let mut test_engine = instantiate();
 
-        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8)));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 8), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
 
-        test_engine.update_non_fungible_data("Shard Ticket", 1, "shard_type", env_args!(Some(ShardType::Clear)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 2, "shard_type", env_args!(Some(ShardType::Yellow)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 3, "shard_type", env_args!(Some(ShardType::Orange)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 4, "shard_type", env_args!(Some(ShardType::Blue)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 5, "shard_type", env_args!(Some(ShardType::Emerald)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 6, "shard_type", env_args!(Some(ShardType::Scrypto)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 7, "shard_type", env_args!(Some(ShardType::Radix)), "admin badge").expect_commit_success();
-        test_engine.update_non_fungible_data("Shard Ticket", 8, "shard_type", env_args!(Some(ShardType::Xian)), "admin badge").expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+        draw_ticket(&mut test_engine, 3, ShardType::Orange);
+        draw_ticket(&mut test_engine, 4, ShardType::Blue);
+        draw_ticket(&mut test_engine, 5, ShardType::Emerald);
+        draw_ticket(&mut test_engine, 6, ShardType::Scrypto);
+        draw_ticket(&mut test_engine, 7, ShardType::Radix);
+        draw_ticket(&mut test_engine, 8, ShardType::Xian);
 
         let ids= nf_ids![1, 2, 3, 4, 5, 6, 7, 8];
-        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()))).expect_commit_success();
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ids.clone()), None::<ManifestProof>)).expect_commit_success();
 
         // 4h = 3600*4*1000 ms
         test_engine.advance_time(3600*4*1000);
-        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", ids))).expect_commit_success();
+        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", ids), None::<ManifestProof>)).expect_commit_success();
 
 
         assert_eq!(test_engine.current_balance( "Shard"), dec!(1000));
@@ -187,4 +352,1339 @@ mod shardz_tests {
         assert_eq!(test_engine.current_balance( "Shard NFT"), dec!(0));
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unbond() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+
+        // 24h = 3600*24*1000 ms
+        test_engine.advance_time(3600*24*1000);
+        test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2, 3]))).expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard"), dec!(1000));
+        assert_eq!(test_engine.current_balance("Shard Ticket"), dec!(0));
+    }
+
+    #[test]
+    fn test_unbond_before_timeout_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+
+        test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2, 3]))).assert_failed_with("Tickets can only be unbonded 24 hours after bonding");
+    }
+
+    #[test]
+    fn test_unbond_drawn_ticket_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 1, ShardType::Blue);
+
+        test_engine.advance_time(3600*24*1000);
+        test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1]))).assert_failed_with("Drawn tickets cannot be unbonded");
+    }
+
+    #[test]
+    fn test_swap_returns_undrawn_tickets() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 2, ShardType::Orange);
+
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2, 3]), None::<ManifestProof>)).expect_commit_success();
+
+        let mut tickets_owned = test_engine.current_ids_balance("Shard Ticket");
+        tickets_owned.sort();
+
+        assert_eq!(tickets_owned, nf_ids![1, 3]);
+        assert_eq!(test_engine.current_ids_balance("Shard NFT"), nf_ids![1]);
+    }
+
+    #[test]
+    fn test_drawn_status() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+
+        let status: IndexMap<NonFungibleLocalId, bool> = test_engine.call_method("drawn_status", env_args!(nf_ids![1, 2])).get_return();
+
+        assert_eq!(status.get(&NonFungibleLocalId::integer(1)), Some(&true));
+        assert_eq!(status.get(&NonFungibleLocalId::integer(2)), Some(&false));
+    }
+
+    #[test]
+    fn test_pending_tickets() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert_eq!(pending, nf_ids![1, 2, 3]);
+
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(1u32)).get_return();
+        assert_eq!(pending, nf_ids![1]);
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert_eq!(pending, nf_ids![1, 3]);
+    }
+
+    #[test]
+    fn test_advance_pending_queue() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+
+        // The head of the queue is still pending so nothing is consumed
+        let removed: u64 = test_engine.call_method("advance_pending_queue", env_args!(10u32)).get_return();
+        assert_eq!(removed, 0);
+
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+
+        let removed: u64 = test_engine.call_method("advance_pending_queue", env_args!(10u32)).get_return();
+        assert_eq!(removed, 2);
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert_eq!(pending, nf_ids![3]);
+    }
+
+    #[test]
+    fn test_stats_count_undrawn_tickets() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        draw_ticket(&mut test_engine, 2, ShardType::Yellow);
+
+        // Ticket 2 is drawn but stays in the queue behind ticket 1
+        let stats: ShardzStats = test_engine.call_method("stats", env_args!()).get_return();
+        assert_eq!(stats.pending_tickets, 2);
+
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        test_engine.advance_time(3600*720*1000);
+        test_engine.call_method("recycle_expired", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![3]))).expect_commit_success();
+
+        let stats: ShardzStats = test_engine.call_method("stats", env_args!()).get_return();
+        assert_eq!(stats.pending_tickets, 0);
+    }
+
+    #[test]
+    fn test_recycle_expired_tickets() {
+        let mut test_engine = instantiate();
+
+        // Ticket 1 is bonded before any draw fee is set
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        test_engine.new_token("fee token", 1000);
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("fee token"), dec!(2)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("set_keeper_tip", env_args!(dec!(5)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 10), Some(Fungible::Bucket("fee token", 20)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+
+        let ticket: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 1);
+        assert_eq!(ticket.expires_at.seconds_since_unix_epoch - ticket.bond_time.seconds_since_unix_epoch, 720*3600);
+        assert_eq!(ticket.treasury_fee, None);
+        // The jackpot takes 10% of the fee of 2, the treasury keeps the rest
+        let ticket: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 2);
+        assert_eq!(ticket.treasury_fee, Some((test_engine.get_resource("fee token"), dec!("1.8"))));
+
+        // Default ticket lifetime: 720h
+        test_engine.advance_time(3600*720*1000);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1]), None::<ManifestProof>))
+            .assert_failed_with("has expired and can only be recycled");
+
+        // Drawn and undrawn tickets are both converted back to SHARD. The tip of 5 is capped at the 1.8 kept by the
+        // treasury for tickets 2 and 3, and the ticket bonded without a fee earns none.
+        test_engine.call_method("recycle_expired", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2, 3]))).expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard"), dec!(992));
+        assert_eq!(test_engine.current_balance("Shard Ticket"), dec!(8));
+        assert_eq!(test_engine.current_balance("fee token"), dec!("983.6"));
+    }
+
+    #[test]
+    fn test_recycle_before_expiry_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+
+        test_engine.advance_time(3600*719*1000);
+        test_engine.call_method("recycle_expired", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2])))
+            .assert_failed_with("has not expired yet");
+    }
+
+    #[test]
+    fn test_recycle_tip_capped_by_treasury() {
+        let mut test_engine = instantiate();
+
+        test_engine.new_token("fee token", 1000);
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("fee token"), dec!(2)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("set_keeper_tip", env_args!(dec!(1)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // The treasury receives 3.6 of the 4 paid, the rest goes to the jackpot
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), Some(Fungible::Bucket("fee token", 4)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        test_engine.call_method_builder("withdraw_treasury", env_args!(test_engine.get_resource("fee token"), dec!(3)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        assert_eq!(test_engine.current_balance("fee token"), dec!(999));
+
+        // Only 0.6 of the tip of 2 is left in the treasury
+        test_engine.advance_time(3600*720*1000);
+        test_engine.call_method("recycle_expired", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2]))).expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard"), dec!(1000));
+        assert_eq!(test_engine.current_balance("fee token"), dec!("999.6"));
+    }
+
+    #[test]
+    fn test_bond_with_draw_fee() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("xrd"), dec!(2)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("set_volume_discounts", env_args!(vec![(5u64, dec!("0.5"))]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        let xrd_before = test_engine.current_balance("xrd");
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), Some(Fungible::Bucket("xrd", 10)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        let xrd_after = test_engine.current_balance("xrd");
+
+        // 3 tickets at 2 XRD each, plus the 1 XRD package royalty and transaction fees
+        assert!(xrd_before - xrd_after > dec!(6));
+        assert!(xrd_before - xrd_after < dec!(8));
+
+        let fee: Decimal = test_engine.call_method("quote_draw_fee", env_args!(10u64)).get_return();
+        assert_eq!(fee, dec!(10));
+    }
+
+    #[test]
+    fn test_bond_with_insufficient_fee_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("xrd"), dec!(2)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), Some(Fungible::Bucket("xrd", 5)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("The draw fee for 3 tickets is 6");
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("A payment of the draw fee is required");
+    }
+
+    #[test]
+    fn test_bond_fee_exempt() {
+        let mut test_engine = instantiate();
+
+        test_engine.new_token("vip badge", 1);
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("xrd"), dec!(2)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("add_fee_exempt_badge", env_args!(test_engine.get_resource("vip badge")))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, Some(Fungible::Proof("vip badge", 1)), None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard Ticket"), dec!(3));
+    }
+
+    /// Bonds and draws a Xian and a Clear shard, for a total voting power of 129
+    fn mint_voting_shards(test_engine: &mut TestEngine) {
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(test_engine, 1, ShardType::Xian);
+        draw_ticket(test_engine, 2, ShardType::Clear);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2]), None::<ManifestProof>)).expect_commit_success();
+    }
+
+    #[test]
+    fn test_governance_proposal_lifecycle() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        let proposal_id: u64 = test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::SetRerollCooldown(1))).get_return();
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard NFT", nf_ids![1, 2]), true)).expect_commit_success();
+
+        let proposal: Proposal = test_engine.call_method("get_proposal", env_args!(proposal_id)).get_return();
+        assert_eq!(proposal.votes_for, 129);
+
+        test_engine.call_method("execute_proposal", env_args!(proposal_id)).assert_failed_with("Proposal is still timelocked");
+
+        // Voting period and timelock: 72h + 24h
+        test_engine.advance_time(3600*96*1000);
+        test_engine.call_method("execute_proposal", env_args!(proposal_id)).expect_commit_success();
+        test_engine.call_method("execute_proposal", env_args!(proposal_id)).assert_failed_with("Proposal was already executed");
+
+        // The new cooldown of 1 hour applies to rerolls
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 3, ShardType::Blue);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![3]), None::<ManifestProof>)).expect_commit_success();
+        test_engine.advance_time(3600*1000);
+        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![3]), None::<ManifestProof>)).expect_commit_success();
+    }
+
+    #[test]
+    fn test_governance_double_vote_fails() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        let proposal_id: u64 = test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::SetUnbondTimeout(48))).get_return();
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard NFT", nf_ids![1]), true)).expect_commit_success();
+
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard NFT", nf_ids![1]), false)).assert_failed_with("Shard #1# already voted");
+    }
+
+    #[test]
+    fn test_governance_quorum_not_reached() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        // A Clear shard alone only has a voting power of 1
+        let proposal_id: u64 = test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![2]), ProposalAction::SetUnbondTimeout(48))).get_return();
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard NFT", nf_ids![2]), true)).expect_commit_success();
+
+        test_engine.advance_time(3600*96*1000);
+        test_engine.call_method("execute_proposal", env_args!(proposal_id)).assert_failed_with("Proposal did not pass");
+    }
+
+    #[test]
+    fn test_create_proposal_without_shard_fails() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![]), ProposalAction::SetUnbondTimeout(48)))
+            .assert_failed_with("A proof of at least one shard is required to create a proposal");
+    }
+
+    #[test]
+    fn test_proposal_shards_back_one_open_proposal() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::SetUnbondTimeout(48)))
+            .expect_commit_success();
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1, 2]), ProposalAction::SetUnbondTimeout(12)))
+            .assert_failed_with("Shard #1# already backs a proposal that is being voted on");
+
+        // Once the vote is over, the shard can back a new proposal
+        test_engine.advance_time(3600*72*1000);
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::SetUnbondTimeout(12)))
+            .expect_commit_success();
+    }
+
+    #[test]
+    fn test_proposal_threshold() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method_builder("set_governance_config", env_args!(GovernanceConfig { proposal_threshold: 100, ..GovernanceConfig::default() }))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // A Clear shard alone only has a voting power of 1
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![2]), ProposalAction::SetUnbondTimeout(48)))
+            .assert_failed_with("The proven shards need a voting power of at least 100 to create a proposal");
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::SetUnbondTimeout(48)))
+            .expect_commit_success();
+    }
+
+    #[test]
+    fn test_tier_rarity_is_capped() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        let tier = CustomTier {
+            name: "Ruby Shard".to_string(),
+            key_image_url: "https://i.ibb.co/23S8X1B/shard-icon.jpg".to_string(),
+            rarity: 40,
+        };
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::AddTier(tier.clone(), 10)))
+            .assert_failed_with("The rarity of a tier cannot exceed 7");
+        test_engine.call_method_builder("add_tier", env_args!(tier, 10u32))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The rarity of a tier cannot exceed 7");
+    }
+
+    #[test]
+    fn test_set_unknown_tier_weight_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method_builder("set_tier_weights", env_args!(vec![(ShardType::Clear, 1u32), (ShardType::Custom(3), 1u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("Unknown tier 3");
+    }
+
+    #[test]
+    fn test_governance_add_tier() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        let tier = CustomTier {
+            name: "Ruby Shard".to_string(),
+            key_image_url: "https://i.ibb.co/23S8X1B/shard-icon.jpg".to_string(),
+            rarity: 7,
+        };
+        let proposal_id: u64 = test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![1]), ProposalAction::AddTier(tier, 10))).get_return();
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard NFT", nf_ids![1]), true)).expect_commit_success();
+        test_engine.advance_time(3600*96*1000);
+        test_engine.call_method("execute_proposal", env_args!(proposal_id)).expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 3, ShardType::Custom(0));
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![3]), None::<ManifestProof>)).expect_commit_success();
+
+        let data: ShardNFT = test_engine.get_non_fungible_data("Shard NFT", 3);
+        assert_eq!(data.shard_type, ShardType::Custom(0));
+    }
+
+    #[test]
+    fn test_jackpot() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("xrd"), dec!(10)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // 10% of the 20 XRD draw fee goes to the jackpot
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), Some(Fungible::Bucket("xrd", 20)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>)).expect_commit_success();
+        let jackpot: Decimal = test_engine.call_method("jackpot_amount", env_args!()).get_return();
+        assert_eq!(jackpot, dec!(2));
+
+        draw_ticket(&mut test_engine, 1, ShardType::Radix);
+        draw_ticket(&mut test_engine, 2, ShardType::Clear);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2]), None::<ManifestProof>)).expect_commit_success();
+
+        // Only the Radix shard wins half of the pool
+        let jackpot: Decimal = test_engine.call_method("jackpot_amount", env_args!()).get_return();
+        assert_eq!(jackpot, dec!(1));
+
+        let wins: Vec<JackpotWin> = test_engine.call_method("jackpot_wins", env_args!(0u64, 10u32)).get_return();
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].nft_id, NonFungibleLocalId::integer(1));
+        assert_eq!(wins[0].amount, dec!(1));
+    }
+
+    #[test]
+    fn test_jackpot_tier_must_be_drawable() {
+        let mut test_engine = instantiate();
+
+        // Xian has no weight by default, so it could never win
+        test_engine.call_method_builder("set_jackpot_config", env_args!(JackpotConfig { tier: ShardType::Xian, ..JackpotConfig::default() }))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The jackpot tier must have a draw weight");
+        test_engine.call_method_builder("set_jackpot_config", env_args!(JackpotConfig { tier: ShardType::Scrypto, ..JackpotConfig::default() }))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+    }
+
+    #[test]
+    fn test_withdraw_former_jackpot_pool() {
+        let mut test_engine = instantiate();
+        test_engine.new_token("fee token", 1000);
+        let fee_token = test_engine.get_resource("fee token");
+
+        test_engine.call_method_builder("set_draw_fee", env_args!(fee_token, dec!(10)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), Some(Fungible::Bucket("fee token", 20)), None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>)).expect_commit_success();
+
+        test_engine.call_method_builder("withdraw_jackpot", env_args!(fee_token))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The pool of the current draw fee resource can only be won");
+
+        // Once the fee is paid in another resource, the pool can no longer be won and is withdrawn by the owner
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("xrd"), dec!(10)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("withdraw_jackpot", env_args!(fee_token))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        assert_eq!(test_engine.current_balance("fee token"), dec!(982));
+
+        test_engine.call_method("withdraw_jackpot", env_args!(fee_token)).assert_failed_with("");
+    }
+
+    #[test]
+    fn test_pity_counter() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        test_engine.call_method_builder("set_pity_config", env_args!(PityConfig { threshold: 2, tier: ShardType::Blue }))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 3), None::<ManifestBucket>, None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<Referrer>))
+            .expect_commit_success();
+
+        // Only Clear shards can be drawn, until the pity counter reaches the threshold
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        draw_ticket(&mut test_engine, 2, ShardType::Clear);
+        let profile: PlayerProfile = test_engine.get_non_fungible_data("Shardz Player Profile", 1);
+        assert_eq!(profile.pity_counter, 2);
+
+        draw_ticket(&mut test_engine, 3, ShardType::Clear);
+        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 3);
+        assert_eq!(ticket_data.shard_type, Some(ShardType::Blue));
+
+        let profile: PlayerProfile = test_engine.get_non_fungible_data("Shardz Player Profile", 1);
+        assert_eq!(profile.pity_counter, 0);
+    }
+
+    #[test]
+    fn test_profile_stats_and_achievements() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<Referrer>))
+            .expect_commit_success();
+        draw_ticket(&mut test_engine, 1, ShardType::Xian);
+        draw_ticket(&mut test_engine, 2, ShardType::Clear);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2]), Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1]))))
+            .expect_commit_success();
+
+        test_engine.advance_time(3600*4*1000);
+        test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![2]), Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1]))))
+            .expect_commit_success();
+
+        let profile: PlayerProfile = test_engine.get_non_fungible_data("Shardz Player Profile", 1);
+        assert_eq!(profile.tickets_bonded, 2);
+        assert_eq!(profile.shards_pulled.get(&ShardType::Xian), Some(&1));
+        assert_eq!(profile.shards_pulled.get(&ShardType::Clear), Some(&1));
+        assert_eq!(profile.rerolls, 1);
+        assert_eq!(profile.achievements, vec![Achievement::FirstShard, Achievement::FirstXian]);
+    }
+
+    #[test]
+    fn test_bond_in_bulk() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 501), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("At most 500 tickets can be bonded per call");
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 500), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        assert_eq!(test_engine.current_ids_balance("Shard Ticket").len(), 500);
+
+        test_engine.call_method_builder("set_max_tickets_per_bond", env_args!(10u64))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("10.5")), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 11), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("At most 10 tickets can be bonded per call");
+    }
+
+    #[test]
+    fn test_whole_units() {
+        assert_eq!(whole_units(dec!(0)), 0);
+        assert_eq!(whole_units(dec!(500)), 500);
+        assert_eq!(whole_units(Decimal::from(u64::MAX)), u64::MAX);
+        assert!(std::panic::catch_unwind(|| whole_units(Decimal::from(u64::MAX) + dec!(1))).is_err());
+    }
+
+    #[test]
+    fn test_bank_fractional_deposits() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("0.7")), None::<ManifestBucket>, None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<Referrer>))
+            .expect_commit_success();
+        let dust: Decimal = test_engine.call_method("banked_dust", env_args!(NonFungibleLocalId::integer(1))).get_return();
+        assert_eq!(dust, dec!("0.7"));
+        assert_eq!(test_engine.current_ids_balance("Shard Ticket").len(), 0);
+
+        // 0.7 banked + 1.5 deposited: two tickets and 0.2 left in the bank
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", dec!("1.5")), None::<ManifestBucket>, None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<Referrer>))
+            .expect_commit_success();
+        assert_eq!(test_engine.current_ids_balance("Shard Ticket").len(), 2);
+        let dust: Decimal = test_engine.call_method("banked_dust", env_args!(NonFungibleLocalId::integer(1))).get_return();
+        assert_eq!(dust, dec!("0.2"));
+
+        test_engine.call_method("withdraw_dust", env_args!(NonFungible::Proof("Shardz Player Profile", nf_ids![1]))).expect_commit_success();
+        assert_eq!(test_engine.current_balance("Shard"), dec!(998));
+    }
+
+    #[test]
+    fn test_profile_is_soulbound() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+
+        test_engine.transfer("user1", "Shardz Player Profile", 1).assert_failed_with("");
+    }
+
+    #[test]
+    fn test_season_tiers() {
+        let mut test_engine = instantiate();
+
+        let pumpkin = CustomTier {
+            name: "Pumpkin Shard".to_string(),
+            key_image_url: "https://i.ibb.co/23S8X1B/shard-icon.jpg".to_string(),
+            rarity: 5,
+        };
+        let start = test_engine.get_current_time();
+        let end = start.add_days(1).unwrap();
+        test_engine.call_method_builder("schedule_season", env_args!("Halloween".to_string(), start, end, vec![(pumpkin, 1_000_000u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        let season: Season = test_engine.call_method("get_season", env_args!(0u64)).get_return();
+        assert_eq!(season.tier_weights, vec![(ShardType::Custom(0), 1_000_000)]);
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+
+        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 1);
+        let seed = ticket_seed(&seed_chain().reveal(&ticket_data.seed_commitment).unwrap(), &ticket_data.bond_hash);
+        let season_weights = vec![(ShardType::Clear, 1), (ShardType::Custom(0), 1_000_000)];
+        assert_eq!(ticket_data.shard_type, Some(draw_tier(&season_weights, &NonFungibleLocalId::integer(1), seed)));
+
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1]), None::<ManifestProof>)).expect_commit_success();
+        let data: ShardNFT = test_engine.get_non_fungible_data("Shard NFT", 1);
+        assert_eq!(data.season_id, Some(0));
+
+        // Once the season is over its tiers cannot be drawn anymore
+        test_engine.advance_time(3600*24*1000);
+        draw_ticket(&mut test_engine, 2, ShardType::Clear);
+        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 2);
+        assert_eq!(ticket_data.shard_type, Some(ShardType::Clear));
+
+        test_engine.call_method_builder("set_tier_weights", env_args!(vec![(ShardType::Custom(0), 1u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("Season tiers can only be drawn during their season");
+    }
+
+    #[test]
+    fn test_ended_season_tiers_cannot_be_minted() {
+        let mut test_engine = instantiate();
+
+        let pumpkin = CustomTier {
+            name: "Pumpkin Shard".to_string(),
+            key_image_url: "https://i.ibb.co/23S8X1B/shard-icon.jpg".to_string(),
+            rarity: 5,
+        };
+        let start = test_engine.get_current_time();
+        test_engine.call_method_builder("schedule_season", env_args!("Halloween".to_string(), start, start.add_days(1).unwrap(), vec![(pumpkin, 1_000_000u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // The season tier outweighs the Clear tier a million to one
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+        draw_ticket(&mut test_engine, 1, ShardType::Clear);
+        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 1);
+        assert_eq!(ticket_data.shard_type, Some(ShardType::Custom(0)));
+
+        // Tickets drawn during the season cannot be swapped for a season shard after its end: they are handed back
+        // while the other tickets of the swap are still minted
+        test_engine.advance_time(3600*24*1000);
+        draw_ticket(&mut test_engine, 2, ShardType::Clear);
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1, 2]), None::<ManifestProof>))
+            .expect_commit_success();
+
+        assert_eq!(test_engine.current_ids_balance("Shard Ticket"), vec![NonFungibleLocalId::integer(1)]);
+        assert_eq!(test_engine.current_balance("Shard NFT"), dec!(1));
+        let data: ShardNFT = test_engine.get_non_fungible_data("Shard NFT", 1);
+        assert_eq!(data.shard_type, ShardType::Clear);
+
+        let user1 = *test_engine.get_account("user1");
+        test_engine.call_method_builder("airdrop_tickets", env_args!(Fungible::Bucket("shard", 1), vec![(user1, 1u32)], Some(ShardType::Custom(0))))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("Season 0 is over, its tiers cannot be minted anymore");
+    }
+
+    #[test]
+    fn test_launch_phases() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        let start = test_engine.get_current_time();
+        let phases = vec![
+            LaunchPhase {
+                name: "Presale".to_string(),
+                start,
+                access: PhaseAccess::Badge(test_engine.get_resource("Shardz Player Profile")),
+                ticket_cap: Some(2),
+                price: Some(dec!(0)),
+            },
+            LaunchPhase {
+                name: "Public".to_string(),
+                start: start.add_days(1).unwrap(),
+                access: PhaseAccess::Public,
+                ticket_cap: None,
+                price: None,
+            },
+        ];
+        test_engine.call_method_builder("set_launch_phases", env_args!(phases))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("A badge is required during this launch phase");
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("Only 2 tickets can be bonded per badge during this launch phase");
+
+        // The public phase does not require any badge
+        test_engine.advance_time(3600*24*1000);
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard Ticket"), dec!(3));
+    }
+
+    #[test]
+    fn test_bond_before_first_launch_phase_fails() {
+        let mut test_engine = instantiate();
+
+        let phases = vec![
+            LaunchPhase {
+                name: "Public".to_string(),
+                start: test_engine.get_current_time().add_days(1).unwrap(),
+                access: PhaseAccess::Public,
+                ticket_cap: None,
+                price: Some(dec!(3)),
+            },
+        ];
+        test_engine.call_method_builder("set_launch_phases", env_args!(phases))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // Read methods keep working before the launch, with the default draw fee
+        let phase: Option<LaunchPhase> = test_engine.call_method("current_phase", env_args!()).get_return();
+        assert_eq!(phase, None);
+        let fee: Decimal = test_engine.call_method("quote_draw_fee", env_args!(2u64)).get_return();
+        assert_eq!(fee, dec!(0));
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("Bonding has not started yet");
+
+        test_engine.advance_time(3600*24*1000);
+        let fee: Decimal = test_engine.call_method("quote_draw_fee", env_args!(2u64)).get_return();
+        assert_eq!(fee, dec!(6));
+    }
+
+    #[test]
+    fn test_launch_phase_allowlist() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        let profile_address = test_engine.get_resource("Shardz Player Profile");
+
+        let phases = vec![
+            LaunchPhase {
+                name: "Allowlist".to_string(),
+                start: test_engine.get_current_time(),
+                access: PhaseAccess::Allowlist,
+                ticket_cap: None,
+                price: None,
+            },
+        ];
+        test_engine.call_method_builder("set_launch_phases", env_args!(phases))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+        test_engine.call_method_builder("add_to_allowlist", env_args!(0u32, vec![NonFungibleGlobalId::new(profile_address, NonFungibleLocalId::integer(1))]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![2])), None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("Badge is not on the allowlist of this launch phase");
+    }
+
+    #[test]
+    fn test_referral_rewards() {
+        let mut test_engine = instantiate();
+
+        // The fee is paid in a token other than XRD so that transaction fees do not affect balances
+        test_engine.new_token("fee token", 1000);
+        test_engine.call_method_builder("set_draw_fee", env_args!(test_engine.get_resource("fee token"), dec!(10)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.set_current_account("user1");
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        test_engine.set_current_account("admin");
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+
+        let referrer = Referrer::Profile(NonFungibleLocalId::integer(1));
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), Some(Fungible::Bucket("fee token", 20)), None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![2])), Some(referrer.clone())))
+            .expect_commit_success();
+
+        // 5% of the 20 tokens draw fee
+        let rewards: Decimal = test_engine.call_method("referral_rewards", env_args!(referrer.clone(), test_engine.get_resource("fee token"))).get_return();
+        assert_eq!(rewards, dec!(1));
+
+        // Only the referrer profile can claim the rewards
+        test_engine.call_method("claim_referral_rewards", env_args!(NonFungible::Proof("Shardz Player Profile", nf_ids![2]), test_engine.get_resource("fee token")))
+            .assert_failed_with("No referral rewards to claim");
+
+        test_engine.set_current_account("user1");
+        test_engine.call_method("claim_referral_rewards", env_args!(NonFungible::Proof("Shardz Player Profile", nf_ids![1]), test_engine.get_resource("fee token")))
+            .expect_commit_success();
+        assert_eq!(test_engine.balance_of("user1", "fee token"), dec!(1));
+    }
+
+    #[test]
+    fn test_fee_shares_cannot_exceed_draw_fee() {
+        let mut test_engine = instantiate();
+
+        // The jackpot already takes 10% of the draw fee
+        test_engine.call_method_builder("set_referral_share", env_args!(dec!("0.95")))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The jackpot and referral shares cannot exceed the draw fee");
+        test_engine.call_method_builder("set_referral_share", env_args!(dec!("0.9")))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method_builder("set_jackpot_config", env_args!(JackpotConfig { fee_share: dec!("0.2"), ..JackpotConfig::default() }))
+            .with_badge("admin badge")
+            .execute()
+            .assert_failed_with("The jackpot and referral shares cannot exceed the draw fee");
+    }
+
+    #[test]
+    fn test_self_referral_fails() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("create_profile", env_args!()).expect_commit_success();
+        let referrer = Referrer::Profile(NonFungibleLocalId::integer(1));
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, Some(NonFungible::Proof("Shardz Player Profile", nf_ids![1])), Some(referrer.clone())))
+            .assert_failed_with("Players cannot refer themselves");
+
+        // Referrals require the profile of the referred player
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 2), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, Some(referrer)))
+            .assert_failed_with("A player profile is required to bond with a referrer");
+    }
+
+    #[test]
+    fn test_airdrop_tickets() {
+        let mut test_engine = instantiate();
+
+        let user1 = *test_engine.get_account("user1");
+        let user2 = *test_engine.get_account("user2");
+        test_engine.call_method_builder("airdrop_tickets", env_args!(Fungible::Bucket("shard", 5), vec![(user1, 2u32), (user2, 1u32)], None::<ShardType>))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        // Airdropped tickets are backed by burned shards
+        assert_eq!(test_engine.current_balance("Shard"), dec!(997));
+        assert_eq!(test_engine.balance_of("user1", "Shard Ticket"), dec!(2));
+        assert_eq!(test_engine.balance_of("user2", "Shard Ticket"), dec!(1));
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert_eq!(pending, nf_ids![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_airdrop_drawn_tickets() {
+        let mut test_engine = instantiate();
+
+        let user1 = *test_engine.get_account("user1");
+        test_engine.call_method_builder("airdrop_tickets", env_args!(Fungible::Bucket("shard", 1), vec![(user1, 1u32)], Some(ShardType::Radix)))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        let ticket_data: ShardTicket = test_engine.get_non_fungible_data("Shard Ticket", 1);
+        assert_eq!(ticket_data.shard_type, Some(ShardType::Radix));
+
+        let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(10u32)).get_return();
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_airdrop_undrawable_tiers_fails() {
+        let mut test_engine = instantiate();
+
+        let user1 = *test_engine.get_account("user1");
+        let airdrop = |test_engine: &mut TestEngine, shard_type: ShardType| {
+            test_engine.call_method_builder("airdrop_tickets", env_args!(Fungible::Bucket("shard", 1), vec![(user1, 1u32)], Some(shard_type)))
+                .with_badge("admin badge")
+                .execute()
+        };
+
+        airdrop(&mut test_engine, ShardType::Custom(5)).assert_failed_with("Unknown tier 5");
+        airdrop(&mut test_engine, ShardType::Emerald).assert_failed_with("Tier Emerald cannot be drawn with the current weights");
+
+        // The jackpot tier cannot be airdropped even when it can be drawn
+        airdrop(&mut test_engine, ShardType::Radix).assert_failed_with("The jackpot tier can only be won through draws");
+    }
+
+    #[test]
+    fn test_airdrop_shards() {
+        let mut test_engine = instantiate();
+
+        let user1 = *test_engine.get_account("user1");
+        test_engine.call_method_builder("airdrop_shards", env_args!(Fungible::Bucket("shard", 10), vec![(user1, dec!("2.5"))]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        assert_eq!(test_engine.balance_of("user1", "Shard"), dec!("2.5"));
+        assert_eq!(test_engine.current_balance("Shard"), dec!("997.5"));
+    }
+
+    #[test]
+    fn test_random_cannot_airdrop() {
+        let mut test_engine = instantiate();
+
+        let user1 = *test_engine.get_account("user1");
+        test_engine.call_method("airdrop_tickets", env_args!(Fungible::Bucket("shard", 1), vec![(user1, 1u32)], None::<ShardType>))
+            .assert_failed_with("");
+    }
+
+    #[test]
+    fn test_rental_pass_votes() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("list_for_rent", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![1]), test_engine.get_resource("xrd"), dec!(5), 24u32))
+            .expect_commit_success();
+        let proposal_id: u64 = test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard NFT", nf_ids![2]), ProposalAction::SetUnbondTimeout(48))).get_return();
+
+        test_engine.set_current_account("user1");
+        test_engine.call_method("rent", env_args!(NonFungibleLocalId::integer(1), 2u32, Fungible::Bucket("xrd", 10))).expect_commit_success();
+
+        let pass: RentalPass = test_engine.get_non_fungible_data("Shard Rental Pass", 1);
+        assert_eq!(pass.shard_type, ShardType::Xian);
+
+        // The renter votes with the power of the rented Xian shard
+        test_engine.call_method("vote", env_args!(proposal_id, NonFungible::Proof("Shard Rental Pass", nf_ids![1]), true)).expect_commit_success();
+        let proposal: Proposal = test_engine.call_method("get_proposal", env_args!(proposal_id)).get_return();
+        assert_eq!(proposal.votes_for, 128);
+
+        test_engine.advance_time(3600*2*1000);
+        test_engine.call_method("create_proposal", env_args!(NonFungible::Proof("Shard Rental Pass", nf_ids![1]), ProposalAction::SetUnbondTimeout(12)))
+            .assert_failed_with("Rental pass has expired");
+    }
+
+    #[test]
+    fn test_reclaim_rental() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("list_for_rent", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![1]), test_engine.get_resource("xrd"), dec!(5), 24u32))
+            .expect_commit_success();
+
+        test_engine.set_current_account("user1");
+        test_engine.call_method("rent", env_args!(NonFungibleLocalId::integer(1), 30u32, Fungible::Bucket("xrd", 150)))
+            .assert_failed_with("Shard can be rented for at most 24 hours");
+        test_engine.call_method("rent", env_args!(NonFungibleLocalId::integer(1), 2u32, Fungible::Bucket("xrd", 10))).expect_commit_success();
+
+        test_engine.set_current_account("admin");
+        test_engine.call_method("reclaim_rental", env_args!(NonFungible::Bucket("Shard Rental Receipt", nf_ids![1])))
+            .assert_failed_with("Shard is still rented");
+
+        test_engine.advance_time(3600*2*1000);
+        let xrd_before = test_engine.current_balance("xrd");
+        test_engine.call_method("reclaim_rental", env_args!(NonFungible::Bucket("Shard Rental Receipt", nf_ids![1]))).expect_commit_success();
+
+        let mut shards_owned = test_engine.current_ids_balance("Shard NFT");
+        shards_owned.sort();
+        assert_eq!(shards_owned, nf_ids![1, 2]);
+        assert!(test_engine.current_balance("xrd") > xrd_before);
+
+        // The same shard can be listed again, under a new receipt
+        test_engine.call_method("list_for_rent", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![1]), test_engine.get_resource("xrd"), dec!(5), 24u32))
+            .expect_commit_success();
+        assert_eq!(test_engine.current_ids_balance("Shard Rental Receipt"), nf_ids![2]);
+        let receipt: RentalReceipt = test_engine.get_non_fungible_data("Shard Rental Receipt", 2);
+        assert_eq!(receipt.shard_id, NonFungibleLocalId::integer(1));
+
+        test_engine.call_method("reclaim_rental", env_args!(NonFungible::Bucket("Shard Rental Receipt", nf_ids![2]))).expect_commit_success();
+        assert_eq!(test_engine.current_balance("Shard NFT"), dec!(2));
+    }
+
+    #[test]
+    fn test_borrow_and_repay() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("supply_liquidity", env_args!(Fungible::Bucket("shard", 100))).expect_commit_success();
+        assert_eq!(test_engine.current_balance("Shard Lending Pool Unit"), dec!(100));
+
+        // A Xian shard lends 5 SHARD
+        test_engine.call_method("borrow", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![1]))).expect_commit_success();
+        assert_eq!(test_engine.current_balance("Shard"), dec!(903));
+        assert_eq!(test_engine.current_ids_balance("Shard Loan Receipt"), nf_ids![1]);
+
+        test_engine.advance_time(3600*10*1000);
+        let owed: Decimal = test_engine.call_method("loan_owed", env_args!(NonFungibleLocalId::integer(1))).get_return();
+        assert_eq!(owed, dec!("5.0005"));
+
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![1]), Fungible::Bucket("shard", 5)))
+            .assert_failed_with("5.0005 SHARD are owed on this loan");
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![1]), Fungible::Bucket("shard", 6)))
+            .expect_commit_success();
+
+        assert_eq!(test_engine.current_balance("Shard"), dec!("897.9995"));
+        let pool_value: Decimal = test_engine.call_method("lending_pool_value", env_args!()).get_return();
+        assert_eq!(pool_value, dec!("100.0005"));
+
+        let mut shards_owned = test_engine.current_ids_balance("Shard NFT");
+        shards_owned.sort();
+        assert_eq!(shards_owned, nf_ids![1, 2]);
+
+        // The same shard backs a second loan, under a new receipt
+        test_engine.call_method("borrow", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![1]))).expect_commit_success();
+        assert_eq!(test_engine.current_ids_balance("Shard Loan Receipt"), nf_ids![2]);
+        let receipt: LoanReceipt = test_engine.get_non_fungible_data("Shard Loan Receipt", 2);
+        assert_eq!(receipt.shard_id, NonFungibleLocalId::integer(1));
+
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![2]), Fungible::Bucket("shard", 6)))
+            .expect_commit_success();
+        assert_eq!(test_engine.current_balance("Shard NFT"), dec!(2));
+    }
+
+    #[test]
+    fn test_liquidate_loan() {
+        let mut test_engine = instantiate();
+        mint_voting_shards(&mut test_engine);
+
+        test_engine.call_method("supply_liquidity", env_args!(Fungible::Bucket("shard", 100))).expect_commit_success();
+        test_engine.call_method("borrow", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![2]))).expect_commit_success();
+
+        test_engine.call_method("liquidate", env_args!(NonFungibleLocalId::integer(2)))
+            .assert_failed_with("Loan is not past its deadline");
+
+        // Default loan duration: 720h
+        test_engine.advance_time(3600*720*1000);
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![2]), Fungible::Bucket("shard", 1)))
+            .assert_failed_with("Loan is past its deadline");
+
+        test_engine.set_current_account("user1");
+        test_engine.call_method("liquidate", env_args!(NonFungibleLocalId::integer(2))).expect_commit_success();
+
+        // The collateral is valued at the principal and interest owed at the deadline
+        test_engine.set_current_account("admin");
+        let pool_value: Decimal = test_engine.call_method("lending_pool_value", env_args!()).get_return();
+        assert_eq!(pool_value, dec!("100.0036"));
+
+        test_engine.call_method("claim_liquidated_shard", env_args!(Fungible::Bucket("Shard Lending Pool Unit", 1), NonFungibleLocalId::integer(2)))
+            .expect_commit_success();
+
+        let mut shards_owned = test_engine.current_ids_balance("Shard NFT");
+        shards_owned.sort();
+        assert_eq!(shards_owned, nf_ids![1, 2]);
+
+        // The claimed shard backs a new loan, which the receipt of the liquidated loan cannot repay
+        test_engine.call_method("borrow", env_args!(NonFungible::Bucket("Shard NFT", nf_ids![2]))).expect_commit_success();
+        let mut receipts = test_engine.current_ids_balance("Shard Loan Receipt");
+        receipts.sort();
+        assert_eq!(receipts, nf_ids![1, 2]);
+
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![1]), Fungible::Bucket("shard", 1)))
+            .assert_failed_with("Loan was liquidated");
+        test_engine.call_method("repay", env_args!(NonFungible::Bucket("Shard Loan Receipt", nf_ids![2]), Fungible::Bucket("shard", 1)))
+            .expect_commit_success();
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut test_engine = instantiate();
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+
+        test_engine.call_method("set_paused", env_args!(true)).assert_failed_with("");
+        test_engine.call_method_builder("set_paused", env_args!(true))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 1), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .assert_failed_with("Shardz is paused");
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1]), None::<ManifestProof>))
+            .assert_failed_with("Shardz is paused");
+
+        let stats: ShardzStats = test_engine.call_method("stats", env_args!()).get_return();
+        assert_eq!(stats.tickets_minted, 1);
+        assert_eq!(stats.pending_tickets, 1);
+        assert_eq!(stats.shards_in_circulation, dec!(999));
+        assert!(stats.paused);
+
+        // Players can still leave while paused
+        test_engine.advance_time(3600*24*1000);
+        test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", nf_ids![1]))).expect_commit_success();
+    }
+
+    #[test]
+    fn test_default_weights_match_published_odds() {
+        let drawn_weights: Vec<(ShardType, u32)> = default_tier_weights().into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+
+        assert_eq!(drawn_weights, PUBLISHED_ODDS.to_vec());
+    }
+
+    #[test]
+    fn test_draw_distribution_matches_published_odds() {
+        let weights = default_tier_weights();
+
+        // Distinct tickets with a constant seed, then a single ticket with distinct seeds
+        let by_ticket: Vec<ShardType> = (1..=50_000u64)
+            .map(|id| draw_tier(&weights, &NonFungibleLocalId::integer(id), 42))
+            .collect();
+        let by_seed: Vec<ShardType> = (0..50_000u64)
+            .map(|seed| draw_tier(&weights, &NonFungibleLocalId::integer(1), seed))
+            .collect();
+
+        assert!(chi_square(&PUBLISHED_ODDS, &by_ticket) < CHI_SQUARE_CRITICAL_5, "Draws by ticket deviate from the published odds");
+        assert!(chi_square(&PUBLISHED_ODDS, &by_seed) < CHI_SQUARE_CRITICAL_5, "Draws by seed deviate from the published odds");
+    }
+
+    #[test]
+    fn test_biased_weights_fail_fairness_check() {
+        // Twice as many Radix shards as published
+        let mut biased = PUBLISHED_ODDS.to_vec();
+        biased[0].1 -= 100;
+        biased[5].1 += 100;
+
+        let draws: Vec<ShardType> = (1..=50_000u64)
+            .map(|id| draw_tier(&biased, &NonFungibleLocalId::integer(id), 42))
+            .collect();
+
+        assert!(chi_square(&PUBLISHED_ODDS, &draws) > CHI_SQUARE_CRITICAL_5);
+
+        // A single Xian shard is enough to reject weights that draw unpublished tiers
+        let mut unpublished = PUBLISHED_ODDS.to_vec();
+        unpublished.push((ShardType::Xian, 10));
+        let draws: Vec<ShardType> = (1..=50_000u64)
+            .map(|id| draw_tier(&unpublished, &NonFungibleLocalId::integer(id), 42))
+            .collect();
+
+        assert_eq!(chi_square(&PUBLISHED_ODDS, &draws), f64::INFINITY);
+    }
+
+    /// Draws every ticket of a range as a Clear shard, in batches that fit in a transaction
+    fn draw_range(test_engine: &mut TestEngine, first: u64, last: u64) {
+        test_engine.call_method_builder("set_tier_weights", env_args!(vec![(ShardType::Clear, 1u32)]))
+            .with_badge("admin badge")
+            .execute()
+            .expect_commit_success();
+
+        for start in (first..=last).step_by(50) {
+            let ticket_ids: Vec<NonFungibleLocalId> = (start..=last.min(start + 49)).map(NonFungibleLocalId::integer).collect();
+            let reveal = next_reveal(test_engine);
+            test_engine.call_method_builder("draw_batch", env_args!(ticket_ids, Some(reveal)))
+                .with_badge("admin badge")
+                .execute()
+                .expect_commit_success();
+        }
+    }
+
+    #[test]
+    fn test_bulk_fee_benchmarks() {
+        let mut test_engine = instantiate();
+        let mut first_ticket = 1u64;
+        let mut costs: Vec<(u64, Decimal, Decimal)> = Vec::new();
+
+        for count in [1u64, 10, 100, 200] {
+            let last_ticket = first_ticket + count - 1;
+            test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", count), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+                .expect_commit_success();
+            draw_range(&mut test_engine, first_ticket, last_ticket);
+
+            let ticket_ids: Vec<NonFungibleLocalId> = (first_ticket..=last_ticket).map(NonFungibleLocalId::integer).collect();
+            let swap_receipt = test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ticket_ids.clone()), None::<ManifestProof>));
+            swap_receipt.expect_commit_success();
+
+            // Every ticket is swapped, so the shards are minted with the same range of ids as the tickets
+            test_engine.advance_time(3600*4*1000);
+            let destroy_receipt = test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", ticket_ids), None::<ManifestProof>));
+            destroy_receipt.expect_commit_success();
+
+            costs.push((count, swap_receipt.fee_summary.total_cost(), destroy_receipt.fee_summary.total_cost()));
+            first_ticket = last_ticket + 1;
+        }
+
+        // Printed with `--nocapture`, to compare the costs across changes of the blueprint
+        println!("items | swap cost (XRD) | per ticket | destroy cost (XRD) | per shard");
+        for (count, swap_cost, destroy_cost) in costs.iter() {
+            println!("{} | {} | {} | {} | {}", count, swap_cost, *swap_cost / Decimal::from(*count), destroy_cost, *destroy_cost / Decimal::from(*count));
+        }
+
+        // A single item call costs what every item cost before batching, bulk calls must stay below it and below the budget
+        let (_, single_swap, single_destroy) = costs[0];
+        let max_cost_per_item = dec!("0.25");
+        for (count, swap_cost, destroy_cost) in costs.iter().skip(1) {
+            let swap_per_item = *swap_cost / Decimal::from(*count);
+            let destroy_per_item = *destroy_cost / Decimal::from(*count);
+
+            assert!(swap_per_item < single_swap, "Swapping {} tickets costs {} XRD per ticket", count, swap_per_item);
+            assert!(destroy_per_item < single_destroy, "Destroying {} shards costs {} XRD per shard", count, destroy_per_item);
+            assert!(swap_per_item <= max_cost_per_item, "Swapping {} tickets costs {} XRD per ticket", count, swap_per_item);
+            assert!(destroy_per_item <= max_cost_per_item, "Destroying {} shards costs {} XRD per shard", count, destroy_per_item);
+        }
+    }
+
+    #[test]
+    fn test_bulk_maximum() {
+        let mut test_engine = instantiate();
+
+        test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", 201), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>))
+            .expect_commit_success();
+        draw_range(&mut test_engine, 1, 201);
+
+        let ticket_ids: Vec<NonFungibleLocalId> = (1..=201).map(NonFungibleLocalId::integer).collect();
+        test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", ticket_ids), None::<ManifestProof>))
+            .assert_failed_with("At most 200 tickets can be swapped per call");
+    }
+
+    #[derive(Debug, Clone)]
+    enum Action {
+        Bond(usize, u64),
+        Draw(usize),
+        Swap(usize),
+        Destroy(usize),
+        Unbond(usize),
+        AdvanceTime(u64),
+    }
+
+    const ACCOUNTS: [&str; 3] = ["admin", "user1", "user2"];
+
+    fn action() -> impl Strategy<Value = Action> {
+        prop_oneof![
+            (0..3usize, 1..20u64).prop_map(|(account, amount)| Action::Bond(account, amount)),
+            (0..8usize).prop_map(Action::Draw),
+            (0..3usize).prop_map(Action::Swap),
+            (0..3usize).prop_map(Action::Destroy),
+            (0..3usize).prop_map(Action::Unbond),
+            (1..30u64).prop_map(Action::AdvanceTime),
+        ]
+    }
+
+    /// Runs an action from its account, calls that cannot succeed in the current state are simply rejected
+    fn run_action(test_engine: &mut TestEngine, action: &Action) {
+        match action {
+            Action::Bond(account, amount) => {
+                test_engine.set_current_account(ACCOUNTS[*account]);
+                test_engine.call_method("bond", env_args!(Fungible::Bucket("shard", *amount), None::<ManifestBucket>, None::<ManifestProof>, None::<ManifestProof>, None::<Referrer>));
+            }
+            Action::Draw(tier) => {
+                test_engine.set_current_account("admin");
+                let pending: Vec<NonFungibleLocalId> = test_engine.call_method("pending_tickets", env_args!(1u32)).get_return();
+                if let Some(NonFungibleLocalId::Integer(ticket_id)) = pending.first() {
+                    draw_ticket(test_engine, ticket_id.value(), default_tier_weights()[*tier].0.clone());
+                }
+            }
+            Action::Swap(account) => {
+                test_engine.set_current_account(ACCOUNTS[*account]);
+                let tickets = test_engine.current_ids_balance("Shard Ticket");
+                if !tickets.is_empty() {
+                    test_engine.call_method("swap_tickets", env_args!(NonFungible::Bucket("Shard Ticket", tickets), None::<ManifestProof>));
+                }
+            }
+            Action::Destroy(account) => {
+                test_engine.set_current_account(ACCOUNTS[*account]);
+                let shards = test_engine.current_ids_balance("Shard NFT");
+                if !shards.is_empty() {
+                    test_engine.call_method("destroy", env_args!(NonFungible::Bucket("Shard NFT", shards), None::<ManifestProof>));
+                }
+            }
+            Action::Unbond(account) => {
+                test_engine.set_current_account(ACCOUNTS[*account]);
+                let tickets = test_engine.current_ids_balance("Shard Ticket");
+                if !tickets.is_empty() {
+                    test_engine.call_method("unbond", env_args!(NonFungible::Bucket("Shard Ticket", tickets)));
+                }
+            }
+            Action::AdvanceTime(hours) => test_engine.advance_time(3600*hours*1000),
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_supply_conservation(actions in prop::collection::vec(action(), 1..25)) {
+            let mut test_engine = instantiate();
+
+            let user1 = *test_engine.get_account("user1");
+            let user2 = *test_engine.get_account("user2");
+            test_engine.call_method_builder("airdrop_shards", env_args!(Fungible::Bucket("shard", 600), vec![(user1, dec!(300)), (user2, dec!(300))]))
+                .with_badge("admin badge")
+                .execute()
+                .expect_commit_success();
+
+            let mut previous: ShardzStats = test_engine.call_method("stats", env_args!()).get_return();
+
+            for action in actions.iter() {
+                run_action(&mut test_engine, action);
+
+                test_engine.set_current_account("admin");
+                let stats: ShardzStats = test_engine.call_method("stats", env_args!()).get_return();
+
+                // Every SHARD is either a token, a ticket or a shard NFT
+                prop_assert_eq!(stats.shards_in_circulation + stats.tickets_in_circulation + stats.nfts_in_circulation, dec!(1000), "after {:?}", action);
+                prop_assert!(stats.tickets_minted >= previous.tickets_minted);
+                prop_assert!(stats.shards_minted >= previous.shards_minted);
+                prop_assert!(stats.pending_tickets <= stats.tickets_minted);
+                prop_assert!(Decimal::from(stats.pending_tickets) <= stats.tickets_in_circulation);
+                previous = stats;
+            }
+        }
+    }
+}